@@ -1,28 +1,72 @@
+use indexmap::IndexMap;
 use proc_macro2::TokenStream;
 use quote::ToTokens;
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 
 pub(crate) enum Expr {
     Ident(syn::Ident),
+    Lit(syn::LitBool),
     Not(NotExpr),
     All(AllExpr),
     Any(AnyExpr),
+    AllExcept(AllExceptExpr),
 }
 
 impl Expr {
     pub fn evaluate(&self, ident: &syn::Ident) -> bool {
         match self {
-            Self::Ident(lit) => ident == lit,
+            // `r#Name` and `Name` refer to the same struct.
+            Self::Ident(lit) => ident.unraw() == lit.unraw(),
+            Self::Lit(lit) => lit.value,
             Self::Not(e) => e.evaluate(ident),
             Self::Any(e) => e.evaluate(ident),
             Self::All(e) => e.evaluate(ident),
+            Self::AllExcept(e) => e.evaluate(ident),
+        }
+    }
+
+    /// All the struct (or group) names referenced by this expression.
+    pub fn idents(&self) -> Vec<&syn::Ident> {
+        match self {
+            Self::Ident(ident) => vec![ident],
+            Self::Lit(_) => Vec::new(),
+            Self::Not(e) => e.expr.idents(),
+            Self::Any(e) => e.exprs.iter().flat_map(Self::idents).collect(),
+            Self::All(e) => e.exprs.iter().flat_map(Self::idents).collect(),
+            Self::AllExcept(e) => e.exprs.iter().flat_map(Self::idents).collect(),
+        }
+    }
+
+    /// Replace each reference to a group from `#[substruct_group]` with an
+    /// `any(...)` expression containing the members of the group.
+    pub fn expand_groups(&mut self, groups: &IndexMap<syn::Ident, Vec<syn::Ident>>) {
+        match self {
+            Self::Ident(ident) => {
+                if let Some(members) = groups.get(ident) {
+                    *self = Self::Any(AnyExpr {
+                        ident: syn::Ident::new("any", ident.span()),
+                        paren: Default::default(),
+                        exprs: members.iter().cloned().map(Self::Ident).collect(),
+                    });
+                }
+            }
+            Self::Lit(_) => (),
+            Self::Not(e) => e.expr.expand_groups(groups),
+            Self::Any(e) => e.exprs.iter_mut().for_each(|e| e.expand_groups(groups)),
+            Self::All(e) => e.exprs.iter_mut().for_each(|e| e.expand_groups(groups)),
+            Self::AllExcept(e) => e.exprs.iter_mut().for_each(|e| e.expand_groups(groups)),
         }
     }
 }
 
 impl Parse for Expr {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::LitBool) {
+            return Ok(Self::Lit(input.parse()?));
+        }
+
         if !input.peek2(syn::token::Paren) {
             return Ok(Self::Ident(input.parse()?));
         }
@@ -33,9 +77,12 @@ impl Parse for Expr {
             _ if ident == "not" => input.parse().map(Self::Not),
             _ if ident == "any" => input.parse().map(Self::Any),
             _ if ident == "all" => input.parse().map(Self::All),
+            _ if ident == "all_except" => input.parse().map(Self::AllExcept),
             _ => Err(syn::Error::new(
                 ident.span(),
-                format!("unexpected operator `{ident}`, expected `not`, `any`, or `all`"),
+                format!(
+                    "unexpected operator `{ident}`, expected `not`, `any`, `all`, or `all_except`"
+                ),
             )),
         }
     }
@@ -45,9 +92,11 @@ impl ToTokens for Expr {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         match self {
             Self::Ident(ident) => ident.to_tokens(tokens),
+            Self::Lit(lit) => lit.to_tokens(tokens),
             Self::Not(e) => e.to_tokens(tokens),
             Self::All(e) => e.to_tokens(tokens),
             Self::Any(e) => e.to_tokens(tokens),
+            Self::AllExcept(e) => e.to_tokens(tokens),
         }
     }
 }
@@ -163,3 +212,44 @@ impl ToTokens for AllExpr {
             .surround(tokens, |tokens| self.exprs.to_tokens(tokens));
     }
 }
+
+/// Equivalent to `not(any(...))`.
+pub(crate) struct AllExceptExpr {
+    pub ident: syn::Ident,
+    pub paren: syn::token::Paren,
+    pub exprs: Punctuated<Expr, syn::Token![,]>,
+}
+
+impl AllExceptExpr {
+    pub fn evaluate(&self, ident: &syn::Ident) -> bool {
+        !self.exprs.iter().any(|e| e.evaluate(ident))
+    }
+}
+
+impl Parse for AllExceptExpr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+
+        let ident: syn::Ident = input.parse()?;
+        if ident != "all_except" {
+            return Err(syn::Error::new(
+                ident.span(),
+                format_args!("expected `all_except`, got `{ident}` instead"),
+            ));
+        }
+
+        Ok(Self {
+            ident,
+            paren: syn::parenthesized!(content in input),
+            exprs: Punctuated::parse_terminated(&content)?,
+        })
+    }
+}
+
+impl ToTokens for AllExceptExpr {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.ident.to_tokens(tokens);
+        self.paren
+            .surround(tokens, |tokens| self.exprs.to_tokens(tokens));
+    }
+}
@@ -62,6 +62,100 @@
 //! for attributes that should only be included in the parent struct, it is
 //! clearer if you use the `#[substruct_attr]` attribute macro documented below.
 //!
+//! This also applies to layout attributes such as `#[repr(C)]`. Derives which
+//! depend on the layout (e.g. `bytemuck::Pod`) are checked against the reduced
+//! set of fields in each generated struct, so make sure to place `#[repr]`
+//! after `#[substruct]` if the generated structs need it as well.
+//!
+//! Fields with a `#[cfg(...)]` attribute are supported as well. The attribute
+//! is copied to the field in each generated struct and to every place the
+//! field is referenced in the generated conversions, including the argument
+//! to `into_<parent>` for excluded fields. The one limitation is that
+//! `fill_default` cannot be used when an excluded field has a `#[cfg]`.
+//!
+//! The one exception is `#[non_exhaustive]`, which is never copied onto the
+//! generated structs since it would prevent other crates from constructing
+//! them. Use `#[substruct_attr(Child, non_exhaustive)]` if a generated struct
+//! should be non-exhaustive as well.
+//!
+//! The `into_<parent>` method takes each excluded field as an argument, in the
+//! same order that the fields are declared in the parent. This also holds for
+//! tuple structs, where the arguments are in ascending order of the index of
+//! the field within the parent:
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(B)]
+//! #[derive(Debug, PartialEq)]
+//! pub struct A(#[substruct(B)] pub i32, pub String, #[substruct(B)] pub i64, pub bool);
+//!
+//! let a = B(1, 2).into_a("text".into(), true);
+//! assert_eq!(a, A(1, "text".into(), 2, true));
+//! ```
+//!
+//! A generated struct which includes none of the parent's fields keeps the
+//! shape of the parent, so it is declared as `struct Marker {}` (or
+//! `struct Marker();` for tuple structs) rather than as a unit struct. Its
+//! `into_<parent>` method takes every field of the parent as an argument:
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(Marker)]
+//! pub struct Message {
+//!     pub id: u32,
+//!     pub body: String,
+//! }
+//!
+//! let message = Marker {}.into_message(1, "body".into());
+//! let marker = Marker::from(message);
+//! ```
+//!
+//! Using `#[substruct]` on a unit struct generates identical unit structs.
+//! Since there are no fields to convert, no conversions are emitted for them.
+//!
+//! Generated unions don't get any conversions either. Reading a field of a
+//! union requires knowing which of its fields is active, which can't be
+//! determined without `unsafe` code. The `constructor` option can be used to
+//! emit a `new_<field>` function for each field of a generated union instead.
+//!
+//! Along with the inherent `into_<parent>` method, conversions are available
+//! through the standard traits:
+//!
+//! - `From<Parent>` is always implemented for each generated struct, so
+//!   `parent.into()` can be used to get any of them. The exceptions are
+//!   structs with fields converted using `via`, which implement `TryFrom`
+//!   instead, and structs with `extra` fields, which need the values of those
+//!   fields.
+//! - `From<Child>` is only implemented for the parent when `into_<parent>`
+//!   takes no arguments. This is the case when the generated struct includes
+//!   all of the parent's fields or when the excluded fields are filled in by
+//!   `fill_default` or `rest_default`.
+//!
+//! The name of `into_<parent>` is only derived from the name of the parent so
+//! it may collide with another method on the generated struct, e.g. one from
+//! a hand-written `impl` block. It can be changed using the `method` option
+//! described below. Code which needs to be independent of these names can use
+//! the trait impls instead.
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(Header, Full)]
+//! #[derive(Clone, Debug, PartialEq)]
+//! pub struct Packet {
+//!     #[substruct(Header, Full)]
+//!     pub id: u32,
+//!     #[substruct(Full)]
+//!     pub body: Vec<u8>,
+//! }
+//!
+//! let packet = Packet { id: 1, body: vec![1, 2] };
+//! let header: Header = packet.clone().into();
+//! let full: Full = packet.clone().into();
+//!
+//! assert_eq!(header.into_packet(vec![1, 2]), packet);
+//! assert_eq!(Packet::from(full), packet);
+//! ```
+//!
 //!
 //! # Overriding documentation for emitted structs and fields
 //! Sometimes you may want to override the emitted documentation for a struct
@@ -105,6 +199,67 @@
 //! }
 //! ```
 //!
+//! Within these doc overrides, `{struct}` is replaced with the name of the
+//! struct being emitted. This is useful when one override applies to several
+//! structs:
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(SearchQuery, ListQuery)]
+//! pub struct Query {
+//!     #[substruct(
+//!         /// The maximum number of results returned by {struct}.
+//!         any(SearchQuery, ListQuery)
+//!     )]
+//!     pub limit: u32,
+//! }
+//! ```
+//!
+//! The inherited documentation can also be removed entirely, leaving the
+//! struct or field undocumented, by using `#[substruct_attr]` with the special
+//! `substruct_nodoc` attribute. This removes all doc comments that would
+//! otherwise be copied from the parent, though a doc override within
+//! `#[substruct]` is still applied. Other `#[doc]` attributes, such as
+//! `#[doc(hidden)]` or `#[doc(alias)]`, are kept.
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(Summary)]
+//! pub struct Report {
+//!     /// Only meaningful within the full report.
+//!     #[substruct(Summary)]
+//!     #[substruct_attr(Summary, substruct_nodoc)]
+//!     pub total: u32,
+//! }
+//! ```
+//!
+//! Besides doc comments, `#[deprecated]` and `#[cfg]` attributes may also be
+//! placed before a struct name within the struct-level `#[substruct]`
+//! attribute. They are added to the generated struct, and `#[cfg]` is also
+//! applied to all of the impls generated for it. `#[deprecated]` can be used
+//! within the `#[substruct]` attribute on a field as well, which deprecates
+//! the field within the matching structs. Any other attribute is an error.
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(
+//!     #[deprecated = "use `UserV2` instead"]
+//!     UserV1,
+//!     UserV2,
+//!     #[cfg(feature = "admin")]
+//!     AdminUser,
+//! )]
+//! pub struct User {
+//!     #[substruct(UserV1, #[deprecated] UserV2, AdminUser)]
+//!     pub name: String,
+//!     #[substruct(UserV2, AdminUser)]
+//!     pub email: String,
+//! }
+//! ```
+//!
+//! Other structs which refer to a struct with `#[cfg]`, e.g. through
+//! `sibling_from` or `parent`, need to be configured the same way.
+//!
 //! # Managing attributes on generated structs
 //! Sometimes you may want attributes to only apply to some of the emitted
 //! structs. To do so, you can use the `#[substruct_attr]` macro to only emit
@@ -129,6 +284,18 @@
 //! }
 //! ```
 //!
+//! Within the string literals of a `#[substruct_attr]` attribute, `{struct}` is
+//! replaced with the name of the struct being emitted, the same as in doc
+//! overrides. For example, `#[substruct_attr(any(B, C), serde(rename =
+//! "{struct}_id"))]` renames the field to `B_id` in `B` and to `C_id` in `C`.
+//!
+//! Attributes such as `#[serde(flatten)]` are kept on every generated struct
+//! that includes the field, so a flattened field round-trips through the
+//! generated conversions unchanged. When a flattened field is excluded from a
+//! generated struct, any keys it would have collected are ignored when
+//! deserializing that struct. Its value has to be supplied separately as an
+//! argument to `into_<parent>`, like any other excluded field.
+//!
 //! For more complicated use cases `#[substruct_attr]` supports a similar
 //! expression language to the `#[cfg]` macro.
 //!
@@ -152,6 +319,15 @@
 //! - `not(<expr>)` - true if the inner expression is false
 //! - `any(<expr>...)` - true if _any_ of the inner expressions are true
 //! - `all(<expr>...)` - true if _all_ of the inner expressions are true
+//! - `all_except(<expr>...)` - true if _none_ of the inner expressions are
+//!   true, the same as `not(any(<expr>...))`
+//! - `true` and `false` - always true or always false, regardless of which
+//!   struct is being emitted
+//!
+//! With no inner expressions, `all()` is always true and `any()` is always
+//! false, the same as `true` and `false`. Since a field using `any()` would
+//! not be included in any of the generated structs, that is reported as an
+//! error.
 //!
 //! On struct fields, the `#[substruct]` entries are implicitly wrapped in an
 //! `any` expression so you can do:
@@ -166,10 +342,66 @@
 //! }
 //! ```
 //!
+//! A field may also have multiple `#[substruct]` attributes, in which case
+//! their entries are merged together as if they were all in one attribute.
+//!
 //! > The parent struct as always implicitly included in the set of structs
 //! > that each field is emitted for. This means that putting `not(A)` in the
 //! > the struct above would not exclude the field from `A` (and is, in fact,
-//! > equivalent to `all()`).
+//! > equivalent to `all()`). The same applies to `false`.
+//!
+//! A field can opt out of this rule with the `exclusive` flag, in which case it
+//! is only emitted in the structs its expressions match and is left out of the
+//! parent entirely. Since the parent has no value for it, `From<Parent>` fills
+//! it in with `Default::default()` and `into_<parent>` discards it. Exclusive
+//! fields cannot be used with tuple structs, `borrow`, or `optional`.
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(Request, Handled)]
+//! #[derive(Clone, Debug)]
+//! pub struct Request {
+//!     #[substruct(Handled)]
+//!     pub path: String,
+//!
+//!     // This field is only available on Handled
+//!     #[substruct(exclusive, Handled)]
+//!     pub status: u16,
+//! }
+//!
+//! let request = Request { path: "/".into() };
+//! let handled = Handled::from(request);
+//! assert_eq!(handled.status, 0);
+//!
+//! let request: Request = handled.into_request();
+//! assert_eq!(request.path, "/");
+//! ```
+//!
+//! An expression on a field which refers to a struct that isn't being
+//! generated, or which doesn't match any of the generated structs (e.g.
+//! `all(B, C)`), is almost certainly a mistake and is reported as an error.
+//! Literal `false` is still allowed.
+//!
+//! Sets of structs that are used together often can be given a name with the
+//! struct-level `#[substruct_group]` attribute. A group name can then be used
+//! anywhere a struct name is accepted and expands to `any(...)` over its
+//! members. Groups may refer to groups defined before them, but a group cannot
+//! share its name with a struct.
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(Parent, A, B, C, D)]
+//! #[substruct_group(Small = (A, B, C))]
+//! pub struct Parent {
+//!     // This field is available on A, B, C, and D
+//!     #[substruct(Small, D)]
+//!     pub f1: u32,
+//!
+//!     // This field is available on D
+//!     #[substruct(all_except(Small))]
+//!     pub f2: u32,
+//! }
+//! ```
 //!
 //! On its own, this isn't too useful, but where it does become useful is when
 //! combined with documentation comment overrides.
@@ -191,7 +423,447 @@
 //! ```
 //!
 //! If multiple documentation overrides apply to a single field, then the first
-//! one to apply will be used.
+//! one to apply will be used. Entries without any documentation are skipped
+//! when picking the override, so in `#[substruct(B, /** Docs */ any(B, C))]`
+//! the field is still documented within `B`.
+//!
+//! # Renaming fields
+//! A field can be given a different name within some of the generated structs
+//! by using the `#[substruct_rename]` attribute. It takes an expression, like
+//! `#[substruct_attr]`, followed by the new name of the field. The generated
+//! conversions map between the two names.
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(Message)]
+//! pub struct HttpRequest {
+//!     #[substruct(Message)]
+//!     #[substruct_rename(Message, payload)]
+//!     pub body: Vec<u8>,
+//!     pub path: String,
+//! }
+//!
+//! let message = Message { payload: vec![1, 2, 3] };
+//! let request = message.into_http_request("/".into());
+//! assert_eq!(request.body, [1, 2, 3]);
+//! ```
+//!
+//! If multiple renames apply to a struct then the first one is used. It is an
+//! error to rename a field in a struct which doesn't include it.
+//!
+//! # Default values for excluded fields
+//! A field which is excluded from a generated struct normally has to be passed
+//! as an argument to `into_<parent>`. The `#[substruct_default]` attribute
+//! takes an expression, like `#[substruct_attr]`, followed by the value that
+//! the field is initialized with instead. This is similar to the `default`
+//! field option described below, but works with any value, not just
+//! `Default::default()`.
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(Request)]
+//! pub struct Config {
+//!     #[substruct(Request)]
+//!     pub url: String,
+//!     #[substruct_default(Request, 30)]
+//!     pub timeout: u32,
+//!     pub retries: u32,
+//! }
+//!
+//! let config = Request { url: "/".into() }.into_config(3);
+//! assert_eq!(config.timeout, 30);
+//! ```
+//!
+//! If multiple defaults apply to a struct then the first one is used. It is an
+//! error to give a default to a field in a struct which includes it.
+//!
+//! # Options for generated structs
+//! The struct-level `#[substruct]` attribute also accepts options which
+//! control how an individual struct is generated. Options apply to the struct
+//! named immediately before them:
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(SubConfig, method_case = "lowercase")]
+//! pub struct HTTPConfig {
+//!     #[substruct(SubConfig)]
+//!     pub port: u16,
+//!     pub host: String,
+//! }
+//!
+//! let config = SubConfig { port: 80 }.into_httpconfig("localhost".into());
+//! ```
+//!
+//! The available options are
+//! - `method_case = "..."` - controls how the parent name is converted when
+//!   generating the `into_<parent>` method. The default is `"snake_case"`
+//!   (`into_http_config`). `"lowercase"` will lowercase the name without
+//!   adding any separators (`into_httpconfig`).
+//! - `method = "..."` - overrides the name of the `into_<parent>` method
+//!   entirely. This takes precedence over `method_case`.
+//! - `where = "T: Trait, ..."` - adds predicates to the where clause of the
+//!   generated struct and to all of the impls generated for it. This is
+//!   useful when the generated struct derives a trait that the parent does
+//!   not.
+//! - `generic_order(...)` - reorders the generic parameters of the generated
+//!   struct. Every generic parameter of the parent must be listed exactly
+//!   once, with the lifetimes before the other parameters. This is useful
+//!   when the generated struct needs to implement a trait which expects its
+//!   parameters in a specific order.
+//! - `borrow` - generates a struct which borrows the included fields of the
+//!   parent instead of owning them. Each field of type `T` becomes a
+//!   `&'borrow T` and a `'borrow` lifetime is added before the other generic
+//!   parameters. Only `From<&'borrow Parent>` is generated since the parent
+//!   cannot be constructed from references. Field transforms cannot be used
+//!   with `borrow`.
+//! - `parent = Sibling` - also emits conversions between the generated
+//!   struct and another generated struct which contains all of its fields,
+//!   as if the sibling were the parent: `into_<sibling>(self, ...)` taking
+//!   the sibling's other fields as arguments, `From<Sibling>`, and
+//!   `From<Self> for Sibling` when the sibling has no other fields. Field
+//!   options cannot be used on the fields of either struct.
+//! - `merges(Sibling)` - emits a `merge(self, other: Sibling, ...)` method
+//!   which constructs the parent from both structs. Fields that are excluded
+//!   from both structs are taken as additional arguments, as with
+//!   `into_<parent>` (including the effect of `ref_args` and `into_args`).
+//!   It is an error for a field to be included in both
+//!   structs. Field options cannot be used on the fields taken from
+//!   `Sibling`.
+//! - `trim_generics` - removes the generic parameters of the parent which are
+//!   not used by any field of the generated struct. See [Generics](#generics)
+//!   for details. This cannot be combined with `borrow`, `optional`,
+//!   `merges`, or `chain_to`.
+//! - `phantom` - adds a `PhantomData` field for each generic parameter of the
+//!   parent which is not used by the generated struct. See
+//!   [Generics](#generics) for details.
+//! - `no_reverse_from` - skips the `From` (or `TryFrom`) impl which converts
+//!   the generated struct into the parent. `into_<parent>` is still emitted.
+//!   This is useful if you want to write that impl yourself.
+//! - `skip_conversions` - skips all conversions between the generated struct
+//!   and the parent: `into_<parent>`, `From<Parent>`, and `From<Child>` are
+//!   not emitted. The struct itself is still emitted as usual. This is useful
+//!   when converting the fields needs custom logic and you want to write the
+//!   conversions yourself. It cannot be combined with options which only
+//!   affect the conversions, such as `method`, `merges`, `parent`,
+//!   `sibling_from`, `partial_eq_parent` or `assert_subset`.
+//! - `optional` - wraps the type of each included field in an `Option`, for
+//!   use as a patch to the parent. Instead of the usual conversions, an
+//!   `apply_to(self, target: &mut Parent)` method is generated which assigns
+//!   each field that is `Some` to the parent. Field transforms cannot be used
+//!   with `optional`. If the generated struct derives `Serialize`, each field
+//!   also gets `#[serde(skip_serializing_if = "Option::is_none")]` so that
+//!   unset fields are left out.
+//! - `default` - derives `Default` for the generated struct, even if the
+//!   parent doesn't implement it. This does nothing if the struct already
+//!   derives `Default`.
+//! - `field_names` - emits a `FIELD_NAMES: &'static [&'static str]` constant
+//!   on the generated struct listing the names of its fields. For tuple
+//!   structs, the names are the field indices.
+//! - `getters` - emits a method returning a reference to each field of the
+//!   generated struct, named after the field. The methods have the same
+//!   visibility as the generated struct. This can only be used on structs
+//!   with named fields.
+//! - `iter` - emits an `iter(&self)` method returning an iterator over
+//!   references to the fields of the generated struct, along with an
+//!   `IntoIterator` impl which iterates over the fields by value. All the
+//!   fields of the generated struct must have the same type, e.g. for vector
+//!   types like `Vec3<T>`, and none of them may use `#[cfg]`.
+//! - `fill_default` - emits a `From` impl converting the generated struct
+//!   into the parent even when some of the parent's fields are excluded. The
+//!   excluded fields are filled in with `Default::default()`.
+//! - `rest_default` - `into_<parent>` no longer takes the excluded fields as
+//!   arguments. Instead, they are filled in from the parent's `Default` impl
+//!   using struct update syntax (`..Default::default()`). Since this also
+//!   makes `into_<parent>` take no arguments, a `From` impl for the parent is
+//!   emitted as well. This cannot be used when the parent is a tuple struct.
+//! - `ref` - also emits a `to_<parent>(&self, ...)` method which takes the
+//!   same arguments as `into_<parent>` but clones each of the fields of the
+//!   generated struct instead of consuming it.
+//! - `roundtrip` - emits a `roundtrip(&self, ...)` method which takes the
+//!   same arguments as `into_<parent>`, converts a clone of `self` into the
+//!   parent and back again, and returns whether the result is equal to
+//!   `self`. This requires the generated struct to implement `Clone` and
+//!   `PartialEq` and is mostly useful for property tests.
+//! - `ref_args` - `into_<parent>` takes a reference to each excluded field
+//!   and clones it, instead of taking the field by value.
+//! - `into_args` - `into_<parent>` takes `impl Into<T>` for each excluded
+//!   field, e.g. allowing a `&str` to be passed for a `String` field.
+//! - `named(a, b, ...)` - gives names to the fields of a tuple parent so
+//!   that the generated struct has named fields instead. The names are
+//!   assigned to the included fields in order, so one must be given for each
+//!   of them. Conversions map each named field back to its index in the
+//!   parent.
+//! - `sibling_from` - emits a `From` impl converting each other generated
+//!   struct which includes exactly the same fields of the parent into this
+//!   one. Fields may be renamed within either struct, but siblings where a
+//!   shared field has field options (e.g. `ty`) are skipped, as are those
+//!   using `borrow`, `optional`, `trim_generics`, `phantom`, or `extra`. Use
+//!   it on both structs to get conversions in both directions.
+//! - `core = Shared` - moves the fields which are included in every struct
+//!   with the same `core` into a separate `Shared` struct. Each of those
+//!   structs then contains the shared struct in a `shared` field (the snake
+//!   case version of its name) and implements `Deref` and `DerefMut` to it, so
+//!   the shared fields can still be accessed directly. The shared struct uses
+//!   the parent's attributes and field attributes, along with the generic
+//!   parameters of the parent that the shared fields use. The shared fields
+//!   cannot be renamed or use field options. This can only be used on
+//!   structs with named fields and cannot be combined with most options that
+//!   change how fields are accessed, such as `borrow` or `merges`.
+//! - `narrow(field: Type, ...)` - changes the type of each listed field to a
+//!   narrower one, e.g. `narrow(count: u32)` for a `u64` field. This is the
+//!   same as using `ty = "u32", via = "try_into"` on the field itself, so the
+//!   struct gets a `TryFrom<Parent>` impl which fails if the value doesn't fit
+//!   and `into_<parent>` widens the value back using `Into`. Each listed field
+//!   must be included in the struct.
+//! - `repr = "..."` - adds a `#[repr(...)]` attribute with the given contents
+//!   (e.g. `repr = "C"` or `repr = "C, align(8)"`) to the generated struct,
+//!   replacing any `#[repr]` copied from the parent. This allows generated
+//!   structs and unions to have a different layout than the parent.
+//! - `max_size = <bytes>` - emits a compile-time assertion that the size of
+//!   the generated struct is at most the given number of bytes. This is
+//!   useful to catch a field being added that pushes the struct past a size
+//!   budget, such as a cache line. It cannot be used on structs with type or
+//!   const parameters.
+//! - `partial_eq_parent` - emits a `PartialEq<Parent>` impl for the generated
+//!   struct which compares only the fields it includes, ignoring the rest of
+//!   the parent's fields. This is mostly useful in test assertions. It cannot
+//!   be combined with `borrow`, `optional`, or field transforms.
+//! - `keep_markers` - adds a `#[substruct_emitted(Child)]` attribute to the
+//!   generated struct and each of its fields (or variants), so that other
+//!   macros running on the generated struct can tell which fields came from
+//!   the parent. The attribute is inert and is registered by a derive which
+//!   is added to the struct along with it. The derive is referred to as
+//!   `::substruct::SubstructEmitted`, so if the dependency has been renamed
+//!   the new path needs to be given with the `crate` option.
+//! - `assert_subset` - emits a compile-time check that every field of the
+//!   generated struct still has the same type as the corresponding field of
+//!   the parent. Fields that are transformed when converting (e.g. using
+//!   `into`, `with`, or `via`) only need to still exist in the parent. This
+//!   catches a field type changing in the parent without the generated struct
+//!   being updated, which may otherwise be hidden by a transform. It can only
+//!   be used on structs.
+//! - `from_ref` - emits a `From<&Child>` impl for the parent which clones
+//!   each of the fields of the generated struct. This requires that all of
+//!   the parent's fields are included in the generated struct.
+//! - `vis = "pub(crate)"` - overrides the visibility of the generated struct.
+//!   By default, generated structs have the same visibility as the parent.
+//! - `method_alias = "..."` - adds a `#[doc(alias = "...")]` to the generated
+//!   `into_<parent>` method so that it can be found under other names in the
+//!   rustdoc search. This can be specified multiple times.
+//! - `conversion_doc = "..."` - replaces the doc comment of the generated
+//!   `into_<parent>` method. Any `{parent}` within the string is replaced with
+//!   the name of the parent, e.g. `conversion_doc = "Wandelt in {parent} um."`.
+//! - `const` - makes the `into_<parent>` method a `const fn`. This cannot be
+//!   used if any of the fields are transformed or filled in with their
+//!   default values, or together with `validate_parent`. The fields of the
+//!   generated struct must also not need to be dropped (e.g. `String` or a
+//!   generic parameter), since destructors cannot currently be run in a
+//!   `const fn`. The `From` impls are unaffected since trait impls cannot be
+//!   `const`.
+//! - `extra(name: Type, ...)` - adds fields to the generated struct which are
+//!   not present in the parent. Since these can't be filled in from the
+//!   parent, the `From<Parent>` impl is replaced by an
+//!   `into_<child>(self, name, ...)` method on the parent which takes the
+//!   extra fields as arguments. The extra fields are dropped when converting
+//!   into the parent. This can only be used on structs with named fields.
+//! - `derive(...)` - adds derives to the generated struct but not the
+//!   parent, e.g. `derive(Serialize, Deserialize)`. The derives are placed
+//!   before all other attributes so that their helper attributes (e.g.
+//!   `#[substruct_attr(Child, serde(...))]`) can be used. This can be
+//!   specified multiple times.
+//! - `method_attr(...)` - adds an attribute to the `into_<parent>` and
+//!   `to_<parent>` methods, after their doc comments, e.g.
+//!   `method_attr(must_use)`. This can be specified multiple times.
+//! - `inline` - shorthand for `method_attr(inline)`.
+//! - `attr(...)` - adds an attribute to the generated struct, e.g.
+//!   `attr(serde(deny_unknown_fields))`. Unlike `#[substruct_attr]`, the
+//!   attribute never appears on the parent. This can be specified multiple
+//!   times.
+//! - `hidden` - adds `#[doc(hidden)]` to the generated struct without
+//!   affecting the parent or any of the other generated structs.
+//! - `impl_marker = "path::to::Trait"` - implements the marker trait for the
+//!   generated struct. The trait must not have any items. This can be
+//!   specified multiple times.
+//! - `validate_parent = "path::to::function"` and `validate_error = "Type"` -
+//!   validates the parent after it is constructed. The function must have the
+//!   signature `fn(&Parent) -> Result<(), Type>`. With these options,
+//!   `into_<parent>` returns `Result<Parent, Type>` instead of `Parent` and,
+//!   if the generated struct has all of the parent's fields, a `TryFrom` impl
+//!   is generated for the parent instead of a `From` impl.
+//! - `checked` - like `validate_parent`, but validates the parent by calling
+//!   its own `validate` method, which must have the signature
+//!   `fn(&self) -> Result<(), Type>`. The error type is still given with
+//!   `validate_error`.
+//! - `chain_to = Leaf` - for use when the generated struct is itself the
+//!   parent of another `#[substruct]` invocation. Generates a direct
+//!   `From<Parent>` impl for `Leaf` along with an `into_leaf` method on the
+//!   parent. `Leaf` must have the same generic parameters as the generated
+//!   struct.
+//!
+//!   ```
+//!   # use substruct::substruct;
+//!   #[substruct(Mid, chain_to = Leaf)]
+//!   #[substruct_attr(Mid, substruct(Leaf))]
+//!   pub struct Full {
+//!       #[substruct(Mid)]
+//!       #[substruct_attr(Mid, substruct(Leaf))]
+//!       pub a: u32,
+//!       #[substruct(Mid)]
+//!       pub b: u32,
+//!       pub c: u32,
+//!   }
+//!
+//!   let leaf = Full { a: 1, b: 2, c: 3 }.into_leaf();
+//!   ```
+//! - `eq_with(field = "path::to::function", ...)` - replaces any inherited
+//!   `#[derive(PartialEq)]` with a hand-written impl where the listed fields
+//!   are compared using `fn(&T, &T) -> bool` and all other fields are
+//!   compared using `==`. This is useful for fields containing floats, where
+//!   `NaN != NaN`.
+//! - `constructor` - emits a `new` function on the generated struct which
+//!   takes each of its fields in order. The function has the same visibility
+//!   as the generated struct.
+//!   Since only one field of a union can be initialized at a time, generated
+//!   unions instead get a `new_<field>` function for each of their fields.
+//! - `builder` - emits a `<Child>Builder` struct with a setter method for each
+//!   field of the generated struct, along with a `Child::builder()` function
+//!   to create one. Calling `build` returns the generated struct, or the name
+//!   of the first field that was not set as an error. This can only be used on
+//!   structs with named fields.
+//! - `cow_enum` - emits a `MaybeOwned<Child><'borrow>` enum with `Owned(Child)`
+//!   and `Borrowed(&'borrow Child)` variants. The enum has a `get` method
+//!   returning `&Child` and an accessor for each field which returns `&T` no
+//!   matter which variant it is. It can be created from either `Child` or
+//!   `&Child` using `From`. The struct must have named fields.
+//! - `debug_entries` - emits a `<child>_entries` method on the parent (e.g.
+//!   `log_fields_entries` for `LogFields`) which returns an iterator over
+//!   `(&'static str, &dyn Debug)` pairs with the name and value of each field
+//!   included in the generated struct. All of those fields must implement
+//!   `Debug`. Fields with a `#[cfg]` are only listed when they are enabled.
+//! - `debug_assert_on_extract = "path::to::function"` - checks the parent with
+//!   `debug_assert!` before converting it into the generated struct. The
+//!   function must have the signature `fn(&Parent) -> bool`. This is useful to
+//!   catch cases where the fields being dropped hold unexpected values. The
+//!   check is compiled out in release builds.
+//!
+//! Some options apply to the whole `#[substruct]` invocation instead of a
+//! single struct. These can be placed anywhere within the arguments:
+//! - `meta` - emits a `substruct_meta` module with a `CHILD_COUNT` constant
+//!   holding the number of generated structs, not counting the parent, along
+//!   with a `<NAME>_FIELDS` constant for each struct (including the parent)
+//!   holding its number of fields. This is mostly useful when writing macros
+//!   on top of `substruct`. The module can be given a different name with
+//!   `meta = name`, which is needed when using it on multiple structs in the
+//!   same module. The field counts are not emitted for enums.
+//! - `summary` - appends a section to the parent struct's documentation which
+//!   lists all the generated structs along with the number of fields in each.
+//! - `profiles(Enum(Layer1, Layer2, ...))` - generates a configuration layer
+//!   struct for each of `Layer1`, `Layer2`, etc. Each layer contains all the
+//!   fields of the parent wrapped in an `Option`. It also generates a
+//!   `Enum::merge_all(base, layer1, layer2, ...)` function which applies each
+//!   layer to `base` in the order they are declared. Fields which are `Some`
+//!   override the value from the previous layers while fields which are
+//!   `None` leave it unchanged. Each layer also gets an `apply_to` method to
+//!   apply only that layer.
+//! - `propagate_derives(...)` - only copies the listed derives from the
+//!   parent's `#[derive(...)]` attributes to the generated structs. The parent
+//!   keeps all of its derives, and derives added with `#[substruct_attr]` are
+//!   not affected.
+//! - `crate = "path"` - the path to the `substruct` crate, used for items the
+//!   generated code refers to (e.g. `crate = "::my_substruct"`). This is only
+//!   needed if the dependency has been renamed in `Cargo.toml`.
+//!
+//! Options can also be used within `#[substruct]` attributes on fields. There
+//! they apply to the expression immediately before them:
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(Small)]
+//! pub struct Big {
+//!     #[substruct(Small)]
+//!     pub id: u64,
+//!     pub name: String,
+//!
+//!     // Not included in Small, but filled in via `Default` instead of being
+//!     // an argument to `into_big`.
+//!     #[substruct(Small, default)]
+//!     pub tags: Vec<String>,
+//! }
+//!
+//! let big = Small { id: 5 }.into_big("five".into());
+//! assert!(big.tags.is_empty());
+//! ```
+//!
+//! The available field options are
+//! - `default` - the field is not included in the matching structs. Instead,
+//!   when converting back to the parent, it is initialized with
+//!   `Default::default()` instead of being passed as an argument.
+//! - `from_fn = "path::to::function"` - when converting back to the parent,
+//!   the parent field is computed by calling the function instead of being
+//!   moved directly. The function is called with the value of the field
+//!   followed by references to each of the `into_<parent>` arguments, in the
+//!   order they are declared in the parent: `fn(T, &A1, &A2, ...) -> T`.
+//! - `with = "path::to::module"` - transforms the value of the field whenever
+//!   converting between the parent and the generated struct. The module must
+//!   contain a `from_parent` function, which is used when converting from the
+//!   parent, and an `into_parent` function, which is used when converting
+//!   back. `with` cannot be combined with `from_fn`.
+//! - `ty = "Type"` - changes the type of the field in the generated struct.
+//!   This needs to be combined with `with` or `into` so that the field can be
+//!   converted between the two types.
+//! - `into` - converts the field using `Into` in both directions. This is
+//!   usually combined with `ty`.
+//! - `map_into` - converts each element of a collection field using `Into` in
+//!   both directions (`into_iter().map(Into::into).collect()`). This is
+//!   usually combined with `ty`, e.g. `ty = "Vec<U>", map_into`.
+//! - `split(a, b, ...)` - replaces the field with several fields in the
+//!   generated struct. Each of them has the visibility and documentation of
+//!   the original field. By default, the field must be a tuple with one
+//!   element for each of the new fields. Other types can be split by giving
+//!   each field a type (`split(a: A, b: B)`) and combining it with `with`. In
+//!   that case, the module's functions have the signatures
+//!   `from_parent(T) -> (A, B, ...)` and `into_parent(A, B, ...) -> T`.
+//!   `split` can only be used in structs with named fields.
+//! - `via = "try_into"` - converts the field from the parent using `TryInto`
+//!   and back into the parent using `Into`. This is usually combined with
+//!   `ty`, e.g. `ty = "u8", via = "try_into"`. Instead of a `From` impl, a
+//!   `TryFrom<Parent>` impl is generated for the struct. Its error type is a
+//!   generated `<Child>ConversionError` enum with a variant for each field
+//!   using `via`, named after the field, which implements `Display` and
+//!   `core::error::Error`. The field's type cannot use any of the parent's
+//!   generic parameters.
+//!
+//! When a transform or `default` involves one of the parent's generic
+//! parameters, the bounds it needs (e.g. `T: Into<U>` or `T: Default`) are
+//! added to the where clause of the generated impls automatically.
+//!
+//! # Enums
+//! `#[substruct]` can also be used on enums. In that case the attribute is
+//! placed on the variants instead of the fields and the generated enums
+//! contain only the variants that are tagged for them. The fields of each
+//! variant are carried through unchanged.
+//!
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(InputEvent)]
+//! #[derive(Debug, PartialEq)]
+//! pub enum Event {
+//!     #[substruct(InputEvent)]
+//!     Key(char),
+//!     #[substruct(InputEvent)]
+//!     Click { x: i32, y: i32 },
+//!     Shutdown,
+//! }
+//!
+//! let event = InputEvent::try_from(Event::Key('a')).unwrap();
+//! assert_eq!(event.into_event(), Event::Key('a'));
+//! assert_eq!(InputEvent::try_from(Event::Shutdown), Err(Event::Shutdown));
+//! ```
+//!
+//! The generated enum can always be converted into the parent using `From` or
+//! `into_<parent>`. Converting from the parent uses `TryFrom`, which returns
+//! the parent value as the error if it is one of the excluded variants. If
+//! every variant is included then a `From` impl is generated instead.
 //!
 //! # Generics
 //! Generics are currently _mostly_ supported. You can use generics with
@@ -218,6 +890,48 @@
 //!     pub text: &'a str,
 //! }
 //! ```
+//!
+//! This can be avoided by using the `trim_generics` option, which removes the
+//! generic parameters that are unused by a generated struct. The conversion
+//! methods declare the removed parameters themselves, so they can still
+//! produce any instance of the parent.
+//! ```
+//! # use substruct::substruct;
+//! #[substruct(NoLifetime, trim_generics)]
+//! pub struct UsesLifetime<'a> {
+//!     #[substruct(NoLifetime)]
+//!     pub name: String,
+//!     pub text: &'a str,
+//! }
+//!
+//! let parent = NoLifetime { name: "name".into() }.into_uses_lifetime("text");
+//! ```
+//!
+//! Alternatively, the `phantom` option keeps the unused parameters and adds a
+//! `PhantomData` field named `__substruct_phantom_<param>` (e.g.
+//! `__substruct_phantom_T` or `__substruct_phantom_a` for `'a`) for each of
+//! them instead. These fields are filled in with `PhantomData` when converting
+//! from the parent and are not taken as arguments by `into_<parent>`.
+//! ```
+//! # use substruct::substruct;
+//! # use std::marker::PhantomData;
+//! #[substruct(NoLifetime, phantom)]
+//! pub struct UsesLifetime<'a> {
+//!     #[substruct(NoLifetime)]
+//!     pub name: String,
+//!     pub text: &'a str,
+//! }
+//!
+//! let child = NoLifetime {
+//!     name: "name".into(),
+//!     __substruct_phantom_a: PhantomData,
+//! };
+//! let parent = child.into_uses_lifetime("text");
+//! ```
+//!
+//! # `no_std`
+//! The generated code only refers to items in `core`, so `#[substruct]` can
+//! be used within `#![no_std]` crates.
 
 use proc_macro::TokenStream;
 
@@ -227,6 +941,7 @@ use proc_macro::TokenStream;
 mod readme {}
 
 mod expr;
+mod options;
 mod substruct;
 
 /// `#[substruct]` attribute macro.
@@ -239,3 +954,11 @@ pub fn substruct(attr: TokenStream, item: TokenStream) -> TokenStream {
         Err(e) => e.to_compile_error().into(),
     }
 }
+
+/// Registers the `#[substruct_emitted]` marker attribute added by the
+/// `keep_markers` option. It does not emit anything itself.
+#[doc(hidden)]
+#[proc_macro_derive(SubstructEmitted, attributes(substruct_emitted))]
+pub fn substruct_emitted(_item: TokenStream) -> TokenStream {
+    TokenStream::new()
+}
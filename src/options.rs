@@ -0,0 +1,204 @@
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+
+/// Bare identifiers which are interpreted as options instead of as struct
+/// names when they appear within `#[substruct]` arguments.
+const FLAGS: &[&str] = &[
+    "assert_subset",
+    "borrow",
+    "builder",
+    "checked",
+    "constructor",
+    "cow_enum",
+    "debug_entries",
+    "default",
+    "exclusive",
+    "field_names",
+    "fill_default",
+    "from_ref",
+    "getters",
+    "hidden",
+    "inline",
+    "into",
+    "into_args",
+    "iter",
+    "keep_markers",
+    "map_into",
+    "meta",
+    "no_reverse_from",
+    "optional",
+    "partial_eq_parent",
+    "phantom",
+    "ref_args",
+    "rest_default",
+    "roundtrip",
+    "sibling_from",
+    "skip_conversions",
+    "summary",
+    "trim_generics",
+];
+
+/// An option within the `#[substruct]` arguments.
+///
+/// ```text
+/// key
+/// key = <value>
+/// key(<tokens>...)
+/// ```
+pub(crate) struct ArgOption {
+    pub name: syn::Ident,
+    pub value: ArgOptionValue,
+}
+
+pub(crate) enum ArgOptionValue {
+    None,
+    Value(syn::Token![=], syn::Expr),
+    List(syn::token::Paren, TokenStream),
+}
+
+impl ArgOption {
+    /// Check whether the next item in the input stream is an option instead of
+    /// an expression.
+    pub fn peek(input: ParseStream) -> bool {
+        // `true` and `false` are expressions, not options.
+        if input.peek(syn::LitBool) {
+            return false;
+        }
+
+        let fork = input.fork();
+        let ident = match syn::Ident::parse_any(&fork) {
+            Ok(ident) => ident,
+            Err(_) => return false,
+        };
+
+        if fork.peek(syn::Token![=]) {
+            return true;
+        }
+
+        if fork.peek(syn::token::Paren) {
+            return !matches!(
+                ident.to_string().as_str(),
+                "not" | "any" | "all" | "all_except"
+            );
+        }
+
+        // Keywords (e.g. `ref`) can never be struct names.
+        if input.fork().parse::<syn::Ident>().is_err() {
+            return true;
+        }
+
+        FLAGS.iter().any(|flag| ident == flag)
+    }
+
+    /// Error out if this option has a value.
+    pub fn expect_flag(&self) -> syn::Result<()> {
+        match &self.value {
+            ArgOptionValue::None => Ok(()),
+            _ => Err(syn::Error::new_spanned(
+                self,
+                format_args!("`{}` does not take a value", self.name),
+            )),
+        }
+    }
+
+    /// Get the value of a `key = "value"` option.
+    pub fn value_str(&self) -> syn::Result<syn::LitStr> {
+        match &self.value {
+            ArgOptionValue::Value(
+                _,
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }),
+            ) => Ok(lit.clone()),
+            _ => Err(syn::Error::new_spanned(
+                self,
+                format_args!("expected `{} = \"...\"`", self.name),
+            )),
+        }
+    }
+
+    /// Get the value of a `key = Ident` option.
+    pub fn value_ident(&self) -> syn::Result<syn::Ident> {
+        match &self.value {
+            ArgOptionValue::Value(_, syn::Expr::Path(path)) if path.qself.is_none() => {
+                match path.path.get_ident() {
+                    Some(ident) => Ok(ident.clone()),
+                    None => Err(syn::Error::new_spanned(path, "expected an identifier")),
+                }
+            }
+            _ => Err(syn::Error::new_spanned(
+                self,
+                format_args!("expected `{} = <ident>`", self.name),
+            )),
+        }
+    }
+
+    /// Parse the contents of a `key(...)` option as a comma-separated list.
+    pub fn parse_list<T: Parse>(&self) -> syn::Result<Punctuated<T, syn::Token![,]>> {
+        match &self.value {
+            ArgOptionValue::List(_, content) => {
+                Punctuated::parse_terminated.parse2(content.clone())
+            }
+            _ => Err(syn::Error::new_spanned(
+                self,
+                format_args!("expected `{}(...)`", self.name),
+            )),
+        }
+    }
+
+    /// Parse the contents of a `key(...)` option.
+    pub fn parse_value<T: Parse>(&self) -> syn::Result<T> {
+        match &self.value {
+            ArgOptionValue::List(_, content) => syn::parse2(content.clone()),
+            _ => Err(syn::Error::new_spanned(
+                self,
+                format_args!("expected `{}(...)`", self.name),
+            )),
+        }
+    }
+
+    pub fn unknown(&self) -> syn::Error {
+        syn::Error::new(
+            self.name.span(),
+            format_args!("unknown #[substruct] option `{}`", self.name),
+        )
+    }
+}
+
+impl Parse for ArgOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = syn::Ident::parse_any(input)?;
+        let value = if input.peek(syn::Token![=]) {
+            ArgOptionValue::Value(input.parse()?, input.parse()?)
+        } else if input.peek(syn::token::Paren) {
+            let content;
+            let paren = syn::parenthesized!(content in input);
+            ArgOptionValue::List(paren, content.parse()?)
+        } else {
+            ArgOptionValue::None
+        };
+
+        Ok(Self { name, value })
+    }
+}
+
+impl ToTokens for ArgOption {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.name.to_tokens(tokens);
+
+        match &self.value {
+            ArgOptionValue::None => (),
+            ArgOptionValue::Value(eq, value) => {
+                eq.to_tokens(tokens);
+                value.to_tokens(tokens);
+            }
+            ArgOptionValue::List(paren, content) => {
+                paren.surround(tokens, |tokens| content.to_tokens(tokens))
+            }
+        }
+    }
+}
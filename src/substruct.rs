@@ -1,62 +1,145 @@
 use std::rc::Rc;
 
-use heck::ToSnakeCase;
-use indexmap::IndexMap;
-use proc_macro2::{Span, TokenStream};
+use heck::{ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use indexmap::{IndexMap, IndexSet};
+use proc_macro2::{Span, TokenStream, TokenTree};
 use quote::ToTokens;
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 
 use crate::expr::Expr;
+use crate::options::{ArgOption, ArgOptionValue};
 
 /// A single input argument to the `#[substruct]` attribute.
 ///
 /// ```text
 /// /// Some doc comment
 /// #[doc = "or doc attribute"]
-/// <expr>
+/// #[deprecated]
+/// <expr>, <option>...
 /// ```
 struct SubstructInputArg {
     docs: Vec<syn::Attribute>,
+
+    /// `#[deprecated]` and `#[cfg]` attributes which are added to the struct or
+    /// field that this argument applies to.
+    attrs: Vec<syn::Attribute>,
     expr: Expr,
+
+    /// Options that follow this argument, up until the next expression.
+    options: Vec<ArgOption>,
+}
+
+impl SubstructInputArg {
+    fn option(&self, name: &str) -> Option<&ArgOption> {
+        self.options.iter().find(|option| option.name == name)
+    }
+
+    fn has_flag(&self, flag: &str) -> bool {
+        self.option(flag).is_some()
+    }
 }
 
 impl Parse for SubstructInputArg {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let attrs = syn::Attribute::parse_outer(input)?;
+        let mut docs = Vec::new();
+        let mut attrs = Vec::new();
+
+        for attr in syn::Attribute::parse_outer(input)? {
+            let path = attr.path();
 
-        for attr in &attrs {
-            if !attr.path().is_ident("doc") {
+            if path.is_ident("doc") {
+                docs.push(attr);
+            } else if path.is_ident("deprecated") || path.is_ident("cfg") {
+                attrs.push(attr);
+            } else {
                 return Err(syn::Error::new_spanned(
-                    &attr,
-                    "only #[doc] attributes are permitted within #[substruct] arguments",
+                    attr,
+                    "only #[doc], #[deprecated], and #[cfg] attributes are permitted within \
+                     #[substruct] arguments",
                 ));
             }
         }
 
         Ok(Self {
-            docs: attrs,
+            docs,
+            attrs,
             expr: input.parse()?,
+            options: Vec::new(),
         })
     }
 }
 
 #[derive(Default)]
 struct SubstructInput {
-    args: Punctuated<SubstructInputArg, syn::Token![,]>,
+    /// Options that come before the first expression.
+    options: Vec<ArgOption>,
+    args: Vec<SubstructInputArg>,
 }
 
 impl SubstructInput {
+    /// Whether the field is excluded from the parent struct.
+    pub fn exclusive(&self) -> bool {
+        self.options.iter().any(|option| option.name == "exclusive")
+    }
+
+    /// Find the first argument that includes the field in the struct `ident`.
     pub fn matching(&self, ident: &syn::Ident) -> Option<&SubstructInputArg> {
-        self.args.iter().find(|arg| arg.expr.evaluate(ident))
+        self.args
+            .iter()
+            .filter(|arg| !arg.has_flag("default"))
+            .find(|arg| arg.expr.evaluate(ident))
+    }
+
+    /// Find the doc override for the field in the struct `ident`. This is the
+    /// docs of the first argument that includes the field and has any, so
+    /// that undocumented arguments (including the implicit one for the
+    /// parent) don't hide later overrides.
+    pub fn docs(&self, ident: &syn::Ident) -> Option<&[syn::Attribute]> {
+        self.args
+            .iter()
+            .filter(|arg| !arg.has_flag("default") && !arg.docs.is_empty())
+            .find(|arg| arg.expr.evaluate(ident))
+            .map(|arg| arg.docs.as_slice())
+    }
+
+    /// Find the extra attributes for the field in the struct `ident`. Like
+    /// with docs, this uses the first argument that includes the field and has
+    /// any.
+    pub fn attrs(&self, ident: &syn::Ident) -> &[syn::Attribute] {
+        self.args
+            .iter()
+            .filter(|arg| !arg.has_flag("default") && !arg.attrs.is_empty())
+            .find(|arg| arg.expr.evaluate(ident))
+            .map(|arg| arg.attrs.as_slice())
+            .unwrap_or_default()
     }
 }
 
 impl Parse for SubstructInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        Ok(Self {
-            args: Punctuated::parse_terminated(input)?,
-        })
+        let mut this = Self::default();
+
+        while !input.is_empty() {
+            if ArgOption::peek(input) {
+                let option = input.parse()?;
+                match this.args.last_mut() {
+                    Some(arg) => arg.options.push(option),
+                    None => this.options.push(option),
+                }
+            } else {
+                this.args.push(input.parse()?);
+            }
+
+            if input.is_empty() {
+                break;
+            }
+
+            let _: syn::Token![,] = input.parse()?;
+        }
+
+        Ok(this)
     }
 }
 
@@ -76,304 +159,5268 @@ impl Parse for SubstructAttrInput {
     }
 }
 
-struct TopLevelArg {
-    docs: Vec<syn::Attribute>,
+/// The arguments to a `#[substruct_rename]` attribute.
+///
+/// ```text
+/// #[substruct_rename(<expr>, <ident>)]
+/// ```
+struct SubstructRenameInput {
+    expr: Expr,
+    _comma: syn::Token![,],
+    ident: syn::Ident,
 }
 
-struct Emitter<'a> {
-    input: &'a syn::DeriveInput,
+impl Parse for SubstructRenameInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            expr: input.parse()?,
+            _comma: input.parse()?,
+            ident: input.parse()?,
+        })
+    }
+}
 
-    /// Use indexmap so that structs are emitted in the order they are specified
-    /// in the macro arguments.
-    args: Rc<IndexMap<syn::Ident, TopLevelArg>>,
+/// The arguments to a `#[substruct_default]` attribute.
+///
+/// ```text
+/// #[substruct_default(<expr>, <value>)]
+/// ```
+struct SubstructDefaultInput {
+    expr: Expr,
+    _comma: syn::Token![,],
+    value: syn::Expr,
+}
 
-    errors: Vec<syn::Error>,
+impl Parse for SubstructDefaultInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            expr: input.parse()?,
+            _comma: input.parse()?,
+            value: input.parse()?,
+        })
+    }
+}
 
-    tokens: TokenStream,
+/// A single group definition within a `#[substruct_group]` attribute.
+///
+/// ```text
+/// <name> = (<ident>, ...)
+/// ```
+struct GroupDef {
+    name: syn::Ident,
+    _eq: syn::Token![=],
+    _paren: syn::token::Paren,
+    members: Punctuated<syn::Ident, syn::Token![,]>,
 }
 
-impl<'a> Emitter<'a> {
-    pub fn from_input(input: &'a syn::DeriveInput, attr: SubstructInput) -> syn::Result<Self> {
-        if let syn::Data::Enum(data) = &input.data {
-            return Err(syn::Error::new(
-                data.enum_token.span,
-                "#[substruct] does not support enums",
-            ));
-        }
+impl Parse for GroupDef {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        Ok(Self {
+            name: input.parse()?,
+            _eq: input.parse()?,
+            _paren: syn::parenthesized!(content in input),
+            members: Punctuated::parse_terminated(&content)?,
+        })
+    }
+}
 
-        let mut errors = Vec::new();
-        let mut args: IndexMap<syn::Ident, TopLevelArg> = attr
-            .args
-            .into_iter()
-            .filter_map(|arg| match arg.expr {
-                Expr::Ident(ident) => Some((ident.clone(), TopLevelArg { docs: arg.docs })),
-                expr => {
-                    errors.push(syn::Error::new_spanned(
-                    expr,
-                    "expressions are not permitted within a struct-level #[substruct] annotation",
-                ));
-                    None
-                }
-            })
-            .collect();
+/// Options which apply to the whole `#[substruct]` invocation instead of a
+/// single generated struct.
+#[derive(Default)]
+struct GlobalOptions {
+    /// Document the generated structs on the parent struct.
+    summary: bool,
 
-        if !args.contains_key(&input.ident) {
-            args.insert(input.ident.clone(), TopLevelArg { docs: Vec::new() });
+    /// The name of a module containing constants that describe the generated
+    /// structs.
+    meta: Option<syn::Ident>,
+
+    /// Configuration layers generated from the parent struct.
+    profiles: Option<Profiles>,
+
+    /// The derives which are copied from the parent to the generated structs.
+    propagate_derives: Option<Vec<syn::Path>>,
+
+    /// The path to the `substruct` crate, if it has been renamed.
+    crate_path: Option<syn::Path>,
+}
+
+impl GlobalOptions {
+    /// Apply `option` if it is a global option. Returns `false` if it is not.
+    fn apply_option(&mut self, option: &ArgOption) -> syn::Result<bool> {
+        match () {
+            _ if option.name == "summary" => {
+                option.expect_flag()?;
+                self.summary = true;
+            }
+            _ if option.name == "meta" => {
+                self.meta = Some(match option.value {
+                    ArgOptionValue::None => syn::Ident::new("substruct_meta", option.name.span()),
+                    _ => option.value_ident()?,
+                });
+            }
+            _ if option.name == "profiles" => {
+                self.profiles = Some(option.parse_value()?);
+            }
+            _ if option.name == "propagate_derives" => {
+                self.propagate_derives = Some(option.parse_list()?.into_iter().collect());
+            }
+            _ if option.name == "crate" => {
+                self.crate_path = Some(option.value_str()?.parse()?);
+            }
+            _ => return Ok(false),
         }
 
+        Ok(true)
+    }
+}
+
+/// The arguments to the `profiles(...)` option.
+///
+/// ```text
+/// <enum>(<layer>, ...)
+/// ```
+struct Profiles {
+    name: syn::Ident,
+    layers: Vec<syn::Ident>,
+}
+
+impl Parse for Profiles {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let name = input.parse()?;
+        syn::parenthesized!(content in input);
+        let layers: Punctuated<syn::Ident, syn::Token![,]> =
+            Punctuated::parse_terminated(&content)?;
+
         Ok(Self {
-            input,
-            args: Rc::new(args),
-            errors,
-            tokens: TokenStream::new(),
+            name,
+            layers: layers.into_iter().collect(),
         })
     }
+}
 
-    pub fn emit(mut self) -> TokenStream {
-        let args = self.args.clone();
-        for name in args.keys() {
-            self.emit_struct(name);
-        }
+struct TopLevelArg {
+    docs: Vec<syn::Attribute>,
 
-        for error in self.errors.drain(..) {
-            self.tokens.extend(error.into_compile_error())
-        }
+    /// `#[deprecated]` and `#[cfg]` attributes given before the name of the
+    /// struct.
+    arg_attrs: Vec<syn::Attribute>,
 
-        self.tokens
-    }
+    /// The visibility of the generated struct, if it differs from the parent.
+    vis: Option<syn::Visibility>,
 
-    fn emit_struct(&mut self, name: &syn::Ident) {
-        let tla = match self.args.get(name) {
-            Some(tla) => tla,
-            None => panic!("Attempted to emit struct `{name}` with no corresponding entry in the top-level arguments")
-        };
+    /// The casing used for the parent name in the `into_<parent>` method.
+    method_case: MethodCase,
 
-        let mut input = self.input.clone();
-        input.ident = name.clone();
+    /// A name which overrides the `into_<parent>` method name entirely.
+    method: Option<syn::Ident>,
 
-        if !tla.docs.is_empty() {
-            input.attrs.retain(|attr| !attr.path().is_ident("doc"));
-            input.attrs.extend_from_slice(&tla.docs);
-        }
+    /// Extra where predicates for the generated struct.
+    where_predicates: Vec<syn::WherePredicate>,
 
-        self.filter_attrs(&mut input.attrs, name);
+    /// The order that generic parameters should be emitted in.
+    generic_order: Option<(syn::Ident, Vec<GenericName>)>,
 
-        match &mut input.data {
-            syn::Data::Enum(_) => return,
-            // syn::Data::Enum(_) => panic!("Attempted to emit substruct on an enum"),
-            syn::Data::Struct(data) => match &mut data.fields {
-                syn::Fields::Named(fields) => self.filter_fields_named(fields, name),
-                syn::Fields::Unnamed(fields) => self.filter_fields_unnamed(fields, name),
-                syn::Fields::Unit => (),
-            },
-            syn::Data::Union(data) => self.filter_fields_named(&mut data.fields, name),
-        };
+    /// `#[doc(alias)]`es for the `into_<parent>` method.
+    method_aliases: Vec<syn::LitStr>,
 
-        input.to_tokens(&mut self.tokens);
+    /// A replacement for the doc comment of the `into_<parent>` method.
+    conversion_doc: Option<syn::LitStr>,
 
-        if input.ident != self.input.ident {
-            self.emit_conversions(&input);
-        }
-    }
+    /// Marker traits to implement for the generated struct.
+    markers: Vec<syn::Path>,
 
-    fn emit_conversions(&mut self, substruct: &syn::DeriveInput) {
-        if !self.errors.is_empty() {
-            return;
-        }
+    /// Extra attributes to add to the generated struct.
+    attrs: Vec<syn::Meta>,
 
-        let original = &self.input.ident;
-        let name = &substruct.ident;
-        let (impl_generics, ty_generics, where_clause) = substruct.generics.split_for_impl();
+    /// Extra derives for the generated struct.
+    derives: Vec<syn::Path>,
 
-        let method = syn::Ident::new(
-            &format!("into_{}", self.input.ident.to_string().to_snake_case()),
-            Span::call_site(),
-        );
-        let doc: syn::Attribute = syn::parse_quote!(
-            #[doc = concat!("Convert `self` into a [`", stringify!(#original), "`].")]
-        );
+    /// Fields which are added to the generated struct but are not present in
+    /// the parent.
+    extras: Vec<(syn::Ident, syn::Type)>,
 
-        let fields = match &self.input.data {
-            syn::Data::Enum(_) => panic!("Attempted to emit conversions for an enum"),
-            // Emitting conversions for an enum doesn't make sense
-            syn::Data::Union(_) => return,
-            // Unit structs have no fields and so they have no conversions
-            syn::Data::Struct(data) if matches!(data.fields, syn::Fields::Unit) => return,
-            syn::Data::Struct(data) => &data.fields,
-        };
+    /// Extra attributes to add to the `into_<parent>` and `to_<parent>`
+    /// methods.
+    method_attrs: Vec<syn::Meta>,
 
-        let mut included = IndexMap::new();
-        let mut excluded = IndexMap::new();
+    /// A function used to validate the parent after it is constructed within
+    /// `into_<parent>`.
+    validate_parent: Option<syn::Path>,
 
-        for (index, mut field) in fields.iter().cloned().enumerate() {
-            let filter = self.filter_field(&mut field, &substruct.ident);
-            let id = match field.ident {
-                Some(ident) => IdentOrIndex::Ident(ident),
-                None => IdentOrIndex::Index(index),
-            };
+    /// The error type returned by `validate_parent`.
+    validate_error: Option<syn::Type>,
 
-            if filter {
-                included.insert(id, field.ty);
-            } else {
-                excluded.insert(id, field.ty);
-            }
-        }
+    /// Whether to validate the parent using its `validate` method within
+    /// `into_<parent>`.
+    checked: bool,
 
-        let args: Vec<_> = excluded
-            .keys()
-            .cloned()
-            .map(|key| key.into_ident())
-            .collect();
-        let types: Vec<_> = excluded.values().collect();
+    /// Structs generated from this struct by another `#[substruct]`
+    /// invocation that the parent can be converted into directly.
+    chain_to: Vec<syn::Ident>,
 
-        let inc_dst: Vec<_> = included.keys().collect();
-        // Renumber source indexes so they refer to the smaller struct
-        let inc_src: Vec<_> = included
-            .keys()
-            .enumerate()
-            .map(|(index, name)| match name {
-                IdentOrIndex::Ident(ident) => IdentOrIndex::Ident(ident.clone()),
-                IdentOrIndex::Index(_) => IdentOrIndex::Index(index),
-            })
-            .collect();
-        let exc: Vec<_> = excluded.keys().collect();
+    /// Custom comparison functions used to generate a `PartialEq` impl in
+    /// place of the derived one.
+    eq_with: Option<Vec<EqWith>>,
 
-        self.tokens.extend(quote::quote! {
-            impl #impl_generics #name #ty_generics
-            #where_clause
-            {
-                #doc
-                pub fn #method(self, #( #args: #types, )*) -> #original #ty_generics {
-                    #original {
-                        #( #inc_dst: self.#inc_src, )*
-                        #( #exc: #args, )*
-                    }
-                }
-            }
-        });
+    /// Whether to emit a `new` constructor taking all the included fields.
+    constructor: bool,
 
-        self.tokens.extend(quote::quote! {
-            impl #impl_generics From<#original #ty_generics> for #name #ty_generics
-            #where_clause
-            {
-                fn from(value: #original #ty_generics) -> Self {
-                    Self {
-                        #( #inc_src: value.#inc_dst, )*
-                    }
-                }
-            }
-        });
+    /// Whether to emit a `<Child>Builder` struct for the generated struct.
+    builder: bool,
 
-        if excluded.is_empty() {
-            self.tokens.extend(quote::quote! {
-                impl #impl_generics From<#name #ty_generics> for #original #ty_generics
-                #where_clause
-                {
-                    fn from(value: #name #ty_generics) -> Self {
-                        value.#method()
-                    }
-                }
-            })
-        }
-    }
+    /// Whether to emit a `FIELD_NAMES` constant for the generated struct.
+    field_names: bool,
 
-    fn filter_fields_named(&mut self, fields: &mut syn::FieldsNamed, name: &syn::Ident) {
-        fields.named = std::mem::take(&mut fields.named)
-            .into_pairs()
-            .filter_map(|mut pair| match self.filter_field(pair.value_mut(), name) {
-                true => Some(pair),
-                false => None,
-            })
-            .collect();
-    }
+    /// Whether to emit a getter method for each field of the generated
+    /// struct.
+    getters: bool,
 
-    fn filter_fields_unnamed(&mut self, fields: &mut syn::FieldsUnnamed, name: &syn::Ident) {
-        fields.unnamed = std::mem::take(&mut fields.unnamed)
-            .into_pairs()
-            .filter_map(|mut pair| match self.filter_field(pair.value_mut(), name) {
-                true => Some(pair),
-                false => None,
-            })
-            .collect();
-    }
+    /// Whether to emit an `iter` method and an `IntoIterator` impl over the
+    /// fields of the generated struct, which must all have the same type.
+    iter: bool,
 
-    fn filter_field(&mut self, field: &mut syn::Field, name: &syn::Ident) -> bool {
-        let substruct: Vec<_> = field
-            .attrs
-            .iter()
-            .filter(|attr| attr.path().is_ident("substruct"))
-            .collect();
+    /// Whether to emit a `MaybeOwned<Child>` enum holding either an owned or
+    /// a borrowed instance of the struct.
+    cow_enum: bool,
 
-        let mut substruct = match substruct {
-            substruct if substruct.is_empty() => Default::default(),
-            substruct => {
-                let args: Option<SubstructInput> = match substruct[0].parse_args() {
-                    Ok(args) => Some(args),
-                    Err(e) => {
-                        self.errors.push(e);
-                        None
-                    }
-                };
+    /// Whether to emit a `<child>_entries` method on the parent which iterates
+    /// over the included fields.
+    debug_entries: bool,
 
-                for attr in &substruct[1..] {
-                    self.errors.push(syn::Error::new_spanned(
-                        attr,
-                        "only one #[substruct] attribute is allowed on a field",
-                    ));
-                }
+    /// A predicate which is checked with `debug_assert!` when converting from
+    /// the parent.
+    debug_assert_on_extract: Option<syn::Path>,
 
-                args.unwrap_or_default()
-            }
-        };
+    /// Whether to emit a `to_<parent>` method which clones the fields of the
+    /// generated struct.
+    by_ref: bool,
 
-        substruct.args.push(SubstructInputArg {
-            docs: Vec::new(),
-            expr: Expr::Ident(self.input.ident.clone()),
-        });
+    /// Whether the `into_<parent>` method is a `const fn`.
+    const_fn: bool,
 
-        let arg = match substruct.matching(name) {
-            Some(arg) => arg,
-            None => return false,
-        };
+    /// Whether `into_<parent>` takes references to the excluded fields and
+    /// clones them.
+    ref_args: bool,
 
-        self.filter_attrs(&mut field.attrs, name);
+    /// Whether `into_<parent>` takes `impl Into<T>` for the excluded fields.
+    into_args: bool,
 
-        if !arg.docs.is_empty() {
-            field.attrs.retain(|attr| !attr.path().is_ident("doc"));
-            field.attrs.extend_from_slice(&arg.docs);
-        }
+    /// Whether to emit a `roundtrip` method which checks that converting
+    /// into the parent and back is lossless.
+    roundtrip: bool,
 
-        true
-    }
+    /// Whether to emit a `From<&Child>` impl for the parent which clones the
+    /// fields of the generated struct.
+    from_ref: bool,
 
-    fn filter_attrs(&mut self, attrs: &mut Vec<syn::Attribute>, name: &syn::Ident) {
-        attrs.retain_mut(|attr| {
-            let path = attr.path();
+    /// Whether to emit a compile-time check that each field of the generated
+    /// struct still matches the corresponding field of the parent.
+    assert_subset: bool,
 
-            if path.is_ident("substruct") {
-                return false;
-            }
+    /// Whether to emit `From` impls from sibling structs which include
+    /// exactly the same fields.
+    sibling_from: bool,
 
-            if !path.is_ident("substruct_attr") {
-                return true;
-            }
+    /// Whether to derive `Default` for the generated struct.
+    derive_default: bool,
 
-            let args: SubstructAttrInput = match attr.parse_args() {
-                Ok(args) => args,
-                Err(e) => {
-                    self.errors.push(e);
-                    return false;
+    /// Whether to emit a `From<Child>` impl for the parent which fills the
+    /// excluded fields with their default values.
+    fill_default: bool,
+
+    /// Whether to skip emitting the `From<Child>` (or `TryFrom<Child>`) impl
+    /// for the parent.
+    no_reverse_from: bool,
+
+    /// Whether to skip emitting all conversions between the generated struct
+    /// and the parent.
+    skip_conversions: bool,
+
+    /// Whether `into_<parent>` fills the excluded fields from the parent's
+    /// `Default` impl instead of taking them as arguments.
+    rest_default: bool,
+
+    /// Whether the generated struct borrows the fields of the parent instead
+    /// of owning them.
+    borrow: bool,
+
+    /// Whether each field of the generated struct is wrapped in an `Option`.
+    optional: bool,
+
+    /// Whether to remove generic parameters that are not used by any of the
+    /// fields of the generated struct.
+    trim_generics: bool,
+
+    /// Whether to add `PhantomData` fields for generic parameters that are not
+    /// used by any of the fields of the generated struct.
+    phantom: bool,
+
+    /// A sibling struct which can be merged with this one to construct the
+    /// parent.
+    merges: Option<syn::Ident>,
+
+    /// A sibling struct which contains all the fields of this one, which
+    /// conversions are generated for as if it were the parent.
+    parent: Option<syn::Ident>,
+
+    /// Names given to the fields of a tuple parent, turning the generated
+    /// struct into one with named fields.
+    named: Option<Vec<syn::Ident>>,
+
+    /// A struct holding the fields shared by every generated struct with the
+    /// same `core`, which this struct contains and derefs to.
+    core: Option<syn::Ident>,
+
+    /// Fields which are narrowed to a smaller type, converted from the parent
+    /// using `TryFrom` and back using `Into`.
+    narrow: Vec<(syn::Ident, syn::Type)>,
+
+    /// The contents of a `#[repr]` attribute which replaces any inherited
+    /// from the parent.
+    repr: Option<Punctuated<syn::Meta, syn::Token![,]>>,
+
+    /// The maximum size of the generated struct in bytes, which is checked at
+    /// compile time.
+    max_size: Option<syn::Expr>,
+
+    /// Whether to mark the generated struct and its fields with
+    /// `#[substruct_emitted]`.
+    keep_markers: bool,
+
+    /// Whether to emit a `PartialEq<Parent>` impl which compares the included
+    /// fields.
+    partial_eq_parent: bool,
+}
+
+impl TopLevelArg {
+    fn new(docs: Vec<syn::Attribute>, arg_attrs: Vec<syn::Attribute>) -> Self {
+        Self {
+            docs,
+            arg_attrs,
+            vis: None,
+            method_case: MethodCase::default(),
+            method: None,
+            where_predicates: Vec::new(),
+            generic_order: None,
+            method_aliases: Vec::new(),
+            conversion_doc: None,
+            markers: Vec::new(),
+            attrs: Vec::new(),
+            method_attrs: Vec::new(),
+            derives: Vec::new(),
+            extras: Vec::new(),
+            validate_parent: None,
+            validate_error: None,
+            checked: false,
+            chain_to: Vec::new(),
+            eq_with: None,
+            constructor: false,
+            builder: false,
+            field_names: false,
+            getters: false,
+            iter: false,
+            cow_enum: false,
+            debug_entries: false,
+            debug_assert_on_extract: None,
+            by_ref: false,
+            const_fn: false,
+            ref_args: false,
+            into_args: false,
+            roundtrip: false,
+            from_ref: false,
+            assert_subset: false,
+            sibling_from: false,
+            derive_default: false,
+            fill_default: false,
+            rest_default: false,
+            no_reverse_from: false,
+            skip_conversions: false,
+            borrow: false,
+            optional: false,
+            trim_generics: false,
+            phantom: false,
+            merges: None,
+            parent: None,
+            named: None,
+            core: None,
+            narrow: Vec::new(),
+            repr: None,
+            max_size: None,
+            keep_markers: false,
+            partial_eq_parent: false,
+        }
+    }
+
+    /// The name of the method which converts the generated struct into the
+    /// parent.
+    fn method_name(&self, original: &syn::Ident) -> syn::Ident {
+        match &self.method {
+            Some(method) => method.clone(),
+            None => syn::Ident::new(
+                &format!("into_{}", self.method_case.apply(original)),
+                Span::call_site(),
+            ),
+        }
+    }
+
+    /// The doc comment of the method which converts the generated struct into
+    /// the parent.
+    fn conversion_doc(&self, original: &syn::Ident) -> String {
+        match &self.conversion_doc {
+            Some(doc) => doc
+                .value()
+                .replace("{parent}", &original.unraw().to_string()),
+            None => format!("Convert `self` into a [`{original}`]."),
+        }
+    }
+
+    fn apply_option(&mut self, option: &ArgOption) -> syn::Result<()> {
+        match () {
+            _ if option.name == "vis" => {
+                let lit = option.value_str()?;
+                self.vis = Some(lit.parse().map_err(|_| {
+                    syn::Error::new(
+                        lit.span(),
+                        format_args!(
+                            "`{}` is not a valid visibility, expected something like `pub(crate)`",
+                            lit.value()
+                        ),
+                    )
+                })?);
+            }
+            _ if option.name == "method" => {
+                let lit = option.value_str()?;
+                self.method = Some(syn::parse_str(&lit.value()).map_err(|_| {
+                    syn::Error::new(
+                        lit.span(),
+                        format_args!("`{}` is not a valid method name", lit.value()),
+                    )
+                })?);
+            }
+            _ if option.name == "method_case" => {
+                self.method_case = MethodCase::from_lit(&option.value_str()?)?;
+            }
+            _ if option.name == "where" => {
+                let predicates = option
+                    .value_str()?
+                    .parse_with(Punctuated::<_, syn::Token![,]>::parse_terminated)?;
+                self.where_predicates.extend(predicates);
+            }
+            _ if option.name == "generic_order" => {
+                let order = option.parse_list()?.into_iter().collect();
+                self.generic_order = Some((option.name.clone(), order));
+            }
+            _ if option.name == "method_alias" => {
+                self.method_aliases.push(option.value_str()?);
+            }
+            _ if option.name == "conversion_doc" => {
+                self.conversion_doc = Some(option.value_str()?);
+            }
+            _ if option.name == "attr" => {
+                self.attrs.push(option.parse_value()?);
+            }
+            _ if option.name == "extra" => {
+                for field in option.parse_list::<SplitField>()? {
+                    let ty = field.ty.ok_or_else(|| {
+                        syn::Error::new(
+                            field.name.span(),
+                            format_args!(
+                                "extra field `{}` needs a type, specify it as `{}: Type`",
+                                field.name, field.name
+                            ),
+                        )
+                    })?;
+                    self.extras.push((field.name, ty));
+                }
+            }
+            _ if option.name == "partial_eq_parent" => {
+                option.expect_flag()?;
+                self.partial_eq_parent = true;
+            }
+            _ if option.name == "keep_markers" => {
+                option.expect_flag()?;
+                self.keep_markers = true;
+            }
+            _ if option.name == "max_size" => match &option.value {
+                ArgOptionValue::Value(_, expr) => self.max_size = Some(expr.clone()),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        option,
+                        "expected `max_size = <bytes>`",
+                    ))
+                }
+            },
+            _ if option.name == "repr" => {
+                let repr = option.value_str()?;
+                self.repr = Some(repr.parse_with(Punctuated::parse_separated_nonempty)?);
+            }
+            _ if option.name == "narrow" => {
+                for field in option.parse_list::<SplitField>()? {
+                    let ty = field.ty.ok_or_else(|| {
+                        syn::Error::new(
+                            field.name.span(),
+                            format_args!(
+                                "narrowed field `{}` needs a type, specify it as `{}: Type`",
+                                field.name, field.name
+                            ),
+                        )
+                    })?;
+                    self.narrow.push((field.name, ty));
+                }
+            }
+            _ if option.name == "derive" => {
+                self.derives.extend(option.parse_list::<syn::Path>()?);
+            }
+            _ if option.name == "method_attr" => {
+                self.method_attrs.push(option.parse_value()?);
+            }
+            _ if option.name == "inline" => {
+                option.expect_flag()?;
+                self.method_attrs.push(syn::parse_quote!(inline));
+            }
+            _ if option.name == "hidden" => {
+                option.expect_flag()?;
+                self.attrs.push(syn::parse_quote!(doc(hidden)));
+            }
+            _ if option.name == "impl_marker" => {
+                self.markers.push(option.value_str()?.parse()?);
+            }
+            _ if option.name == "validate_parent" => {
+                self.validate_parent = Some(option.value_str()?.parse()?);
+            }
+            _ if option.name == "validate_error" => {
+                self.validate_error = Some(option.value_str()?.parse()?);
+            }
+            _ if option.name == "checked" => {
+                option.expect_flag()?;
+                self.checked = true;
+            }
+            _ if option.name == "chain_to" => {
+                self.chain_to.push(option.value_ident()?);
+            }
+            _ if option.name == "eq_with" => {
+                self.eq_with = Some(option.parse_list()?.into_iter().collect());
+            }
+            _ if option.name == "constructor" => {
+                option.expect_flag()?;
+                self.constructor = true;
+            }
+            _ if option.name == "getters" => {
+                option.expect_flag()?;
+                self.getters = true;
+            }
+            _ if option.name == "iter" => {
+                option.expect_flag()?;
+                self.iter = true;
+            }
+            _ if option.name == "field_names" => {
+                option.expect_flag()?;
+                self.field_names = true;
+            }
+            _ if option.name == "builder" => {
+                option.expect_flag()?;
+                self.builder = true;
+            }
+            _ if option.name == "cow_enum" => {
+                option.expect_flag()?;
+                self.cow_enum = true;
+            }
+            _ if option.name == "debug_entries" => {
+                option.expect_flag()?;
+                self.debug_entries = true;
+            }
+            _ if option.name == "default" => {
+                option.expect_flag()?;
+                self.derive_default = true;
+            }
+            _ if option.name == "fill_default" => {
+                option.expect_flag()?;
+                self.fill_default = true;
+            }
+            _ if option.name == "no_reverse_from" => {
+                option.expect_flag()?;
+                self.no_reverse_from = true;
+            }
+            _ if option.name == "skip_conversions" => {
+                option.expect_flag()?;
+                self.skip_conversions = true;
+            }
+            _ if option.name == "rest_default" => {
+                option.expect_flag()?;
+                self.rest_default = true;
+            }
+            _ if option.name == "merges" => {
+                self.merges = Some(option.parse_value()?);
+            }
+            _ if option.name == "parent" => {
+                self.parent = Some(option.value_ident()?);
+            }
+            _ if option.name == "phantom" => {
+                option.expect_flag()?;
+                self.phantom = true;
+            }
+            _ if option.name == "trim_generics" => {
+                option.expect_flag()?;
+                self.trim_generics = true;
+            }
+            _ if option.name == "optional" => {
+                option.expect_flag()?;
+                self.optional = true;
+            }
+            _ if option.name == "borrow" => {
+                option.expect_flag()?;
+                self.borrow = true;
+            }
+            _ if option.name == "ref" => {
+                option.expect_flag()?;
+                self.by_ref = true;
+            }
+            _ if option.name == "const" => {
+                option.expect_flag()?;
+                self.const_fn = true;
+            }
+            _ if option.name == "ref_args" => {
+                option.expect_flag()?;
+                self.ref_args = true;
+            }
+            _ if option.name == "into_args" => {
+                option.expect_flag()?;
+                self.into_args = true;
+            }
+            _ if option.name == "roundtrip" => {
+                option.expect_flag()?;
+                self.roundtrip = true;
+            }
+            _ if option.name == "from_ref" => {
+                option.expect_flag()?;
+                self.from_ref = true;
+            }
+            _ if option.name == "core" => {
+                self.core = Some(option.value_ident()?);
+            }
+            _ if option.name == "named" => {
+                self.named = Some(option.parse_list::<syn::Ident>()?.into_iter().collect());
+            }
+            _ if option.name == "sibling_from" => {
+                option.expect_flag()?;
+                self.sibling_from = true;
+            }
+            _ if option.name == "assert_subset" => {
+                option.expect_flag()?;
+                self.assert_subset = true;
+            }
+            _ if option.name == "debug_assert_on_extract" => {
+                self.debug_assert_on_extract = Some(option.value_str()?.parse()?);
+            }
+            _ => return Err(option.unknown()),
+        }
+
+        Ok(())
+    }
+}
+
+/// A single field comparison within `eq_with(...)`.
+///
+/// ```text
+/// <field> = "path::to::function"
+/// ```
+struct EqWith {
+    member: syn::Member,
+    _eq: syn::Token![=],
+    func: syn::LitStr,
+}
+
+impl Parse for EqWith {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            member: input.parse()?,
+            _eq: input.parse()?,
+            func: input.parse()?,
+        })
+    }
+}
+
+/// A single field within the `split(...)` field option.
+///
+/// ```text
+/// <name>
+/// <name>: <type>
+/// ```
+struct SplitField {
+    name: syn::Ident,
+    ty: Option<syn::Type>,
+}
+
+impl Parse for SplitField {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let ty = match input.parse::<Option<syn::Token![:]>>()? {
+            Some(_) => Some(input.parse()?),
+            None => None,
+        };
+
+        Ok(Self { name, ty })
+    }
+}
+
+/// The name of a generic parameter, as used in `generic_order(...)`.
+enum GenericName {
+    Lifetime(syn::Lifetime),
+    Ident(syn::Ident),
+}
+
+impl GenericName {
+    fn matches(&self, param: &syn::GenericParam) -> bool {
+        match (self, param) {
+            (Self::Lifetime(lt), syn::GenericParam::Lifetime(param)) => *lt == param.lifetime,
+            (Self::Ident(ident), syn::GenericParam::Type(param)) => *ident == param.ident,
+            (Self::Ident(ident), syn::GenericParam::Const(param)) => *ident == param.ident,
+            _ => false,
+        }
+    }
+}
+
+impl Parse for GenericName {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::Lifetime) {
+            input.parse().map(Self::Lifetime)
+        } else {
+            input.parse().map(Self::Ident)
+        }
+    }
+}
+
+impl ToTokens for GenericName {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Lifetime(lt) => lt.to_tokens(tokens),
+            Self::Ident(ident) => ident.to_tokens(tokens),
+        }
+    }
+}
+
+/// Reorder `generics` so that its parameters match the order in `order`.
+///
+/// `order` must name every generic parameter exactly once.
+fn reorder_generics(
+    generics: &mut syn::Generics,
+    span: &syn::Ident,
+    order: &[GenericName],
+) -> syn::Result<()> {
+    let mut params: Vec<_> = generics.params.iter().cloned().collect();
+    let mut reordered = Vec::with_capacity(params.len());
+
+    for name in order {
+        match params.iter().position(|param| name.matches(param)) {
+            Some(index) => reordered.push(params.remove(index)),
+            None => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "not a generic parameter of the parent struct (or specified multiple times)",
+                ))
+            }
+        }
+    }
+
+    if let Some(param) = params.first() {
+        return Err(syn::Error::new(
+            span.span(),
+            format_args!(
+                "generic_order is missing the generic parameter `{}`",
+                param.to_token_stream()
+            ),
+        ));
+    }
+
+    // Lifetimes always have to come before the type and const parameters.
+    let misplaced = reordered.windows(2).find(|pair| {
+        !matches!(pair[0], syn::GenericParam::Lifetime(_))
+            && matches!(pair[1], syn::GenericParam::Lifetime(_))
+    });
+    if let Some(pair) = misplaced {
+        return Err(syn::Error::new(
+            span.span(),
+            format_args!(
+                "generic_order must list the lifetime `{}` before the type and const parameters",
+                pair[1].to_token_stream()
+            ),
+        ));
+    }
+
+    generics.params = reordered.into_iter().collect();
+    Ok(())
+}
+
+/// How the parent struct name is converted when building the name of the
+/// `into_<parent>` method.
+#[derive(Copy, Clone, Debug, Default)]
+enum MethodCase {
+    /// `HTTPConfig` becomes `into_http_config`.
+    #[default]
+    Snake,
+
+    /// `HTTPConfig` becomes `into_httpconfig`.
+    Lower,
+}
+
+impl MethodCase {
+    fn from_lit(lit: &syn::LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "snake_case" => Ok(Self::Snake),
+            "lowercase" => Ok(Self::Lower),
+            value => Err(syn::Error::new(
+                lit.span(),
+                format_args!("unknown method case `{value}`, expected `snake_case` or `lowercase`"),
+            )),
+        }
+    }
+
+    fn apply(self, ident: &syn::Ident) -> String {
+        let name = ident.unraw().to_string();
+
+        match self {
+            Self::Snake => name.to_snake_case(),
+            Self::Lower => name.to_lowercase(),
+        }
+    }
+}
+
+struct Emitter<'a> {
+    input: &'a syn::DeriveInput,
+
+    /// Use indexmap so that structs are emitted in the order they are specified
+    /// in the macro arguments.
+    args: Rc<IndexMap<syn::Ident, TopLevelArg>>,
+    options: GlobalOptions,
+
+    /// Extra attributes to add to the parent struct.
+    parent_attrs: Vec<syn::Attribute>,
+
+    /// Groups of structs defined with `#[substruct_group]`.
+    groups: IndexMap<syn::Ident, Vec<syn::Ident>>,
+
+    /// The parsed `#[substruct]` attributes of each field and enum variant of
+    /// the parent, keyed by the tokens of those attributes.
+    field_inputs: IndexMap<String, Rc<SubstructInput>>,
+
+    errors: Vec<syn::Error>,
+
+    tokens: TokenStream,
+}
+
+impl<'a> Emitter<'a> {
+    pub fn from_input(input: &'a syn::DeriveInput, attr: SubstructInput) -> syn::Result<Self> {
+        let mut errors = Vec::new();
+        let mut options = GlobalOptions::default();
+        let mut args: IndexMap<syn::Ident, TopLevelArg> = IndexMap::new();
+
+        for option in &attr.options {
+            match options.apply_option(option) {
+                Ok(true) => (),
+                Ok(false) => errors.push(option.unknown()),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        for arg in attr.args {
+            let ident = match arg.expr {
+                Expr::Ident(ident) => ident,
+                expr => {
+                    errors.push(syn::Error::new_spanned(
+                        expr,
+                        "expressions are not permitted within a struct-level #[substruct] annotation",
+                    ));
+                    continue;
+                }
+            };
+
+            let mut tla = TopLevelArg::new(arg.docs, arg.attrs);
+            for option in &arg.options {
+                let result = match options.apply_option(option) {
+                    Ok(true) => Ok(()),
+                    Ok(false) => tla.apply_option(option),
+                    Err(e) => Err(e),
+                };
+
+                if let Err(e) = result {
+                    errors.push(e);
+                }
+            }
+
+            args.insert(ident, tla);
+        }
+
+        if !args.contains_key(&input.ident) {
+            args.insert(
+                input.ident.clone(),
+                TopLevelArg::new(Vec::new(), Vec::new()),
+            );
+        }
+
+        let mut groups: IndexMap<syn::Ident, Vec<syn::Ident>> = IndexMap::new();
+        for attr in &input.attrs {
+            if !attr.path().is_ident("substruct_group") {
+                continue;
+            }
+
+            let defs = match attr
+                .parse_args_with(Punctuated::<GroupDef, syn::Token![,]>::parse_terminated)
+            {
+                Ok(defs) => defs,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+
+            for def in defs {
+                if args.contains_key(&def.name) {
+                    errors.push(syn::Error::new(
+                        def.name.span(),
+                        format_args!("group `{}` has the same name as a struct", def.name),
+                    ));
+                    continue;
+                }
+
+                // Groups may refer to groups that were defined before them.
+                let members = def
+                    .members
+                    .into_iter()
+                    .flat_map(|member| match groups.get(&member) {
+                        Some(members) => members.clone(),
+                        None => vec![member],
+                    })
+                    .collect();
+
+                groups.insert(def.name, members);
+            }
+        }
+
+        let mut emitter = Self {
+            input,
+            args: Rc::new(args),
+            options,
+            parent_attrs: Vec::new(),
+            groups,
+            field_inputs: IndexMap::new(),
+            errors,
+            tokens: TokenStream::new(),
+        };
+        emitter.parse_field_inputs();
+
+        Ok(emitter)
+    }
+
+    pub fn emit(mut self) -> TokenStream {
+        let args = self.args.clone();
+
+        if self.options.summary {
+            self.emit_summary();
+        }
+
+        if let Some(module) = self.options.meta.take() {
+            self.emit_meta(&module);
+        }
+
+        self.check_field_exprs();
+        self.emit_cores();
+
+        for name in args.keys() {
+            self.emit_struct(name);
+        }
+
+        if let Some(profiles) = self.options.profiles.take() {
+            self.emit_profiles(&profiles);
+        }
+
+        for error in self.errors.drain(..) {
+            self.tokens.extend(error.into_compile_error())
+        }
+
+        self.tokens
+    }
+
+    /// Emit the layer structs and profile enum for the `profiles(...)` option.
+    ///
+    /// Each layer contains every field of the parent wrapped in an `Option`.
+    /// Layers are merged in the order they are declared, with `Some` fields
+    /// overriding the value from previous layers and `None` fields leaving it
+    /// unchanged.
+    fn emit_profiles(&mut self, profiles: &Profiles) {
+        let original = &self.input.ident;
+        let enum_name = &profiles.name;
+        let (impl_generics, ty_generics, where_clause) = self.input.generics.split_for_impl();
+
+        let fields = match &self.input.data {
+            syn::Data::Struct(data) if !matches!(data.fields, syn::Fields::Unit) => &data.fields,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    enum_name.span(),
+                    "profiles can only be used on structs with fields",
+                ));
+                return;
+            }
+        };
+
+        for layer in &profiles.layers {
+            if self.args.contains_key(layer) {
+                self.errors.push(syn::Error::new(
+                    layer.span(),
+                    format_args!("`{layer}` is already a generated struct"),
+                ));
+            }
+        }
+
+        // Exclusive fields are not part of the parent so they have no place in
+        // its layers.
+        let exclusive: Vec<_> = fields
+            .iter()
+            .map(|field| self.field_input(&field.attrs).exclusive())
+            .collect();
+        // Tuple fields are numbered after the exclusive fields are removed.
+        let members: Vec<_> = fields
+            .iter()
+            .zip(&exclusive)
+            .filter(|&(_, exclusive)| !exclusive)
+            .enumerate()
+            .map(|(index, (field, _))| match &field.ident {
+                Some(ident) => IdentOrIndex::Ident(ident.clone()),
+                None => IdentOrIndex::Index(index),
+            })
+            .collect();
+
+        for layer in &profiles.layers {
+            let mut input = self.input.clone();
+            input.ident = layer.clone();
+            self.filter_attrs(&mut input.attrs, layer);
+
+            if let syn::Data::Struct(data) = &mut input.data {
+                let punctuated = match &mut data.fields {
+                    syn::Fields::Named(fields) => Some(&mut fields.named),
+                    syn::Fields::Unnamed(fields) => Some(&mut fields.unnamed),
+                    syn::Fields::Unit => None,
+                };
+
+                if let Some(fields) = punctuated {
+                    let mut index = 0;
+                    *fields = std::mem::take(fields)
+                        .into_pairs()
+                        .filter(|_| {
+                            index += 1;
+                            !exclusive[index - 1]
+                        })
+                        .collect();
+                }
+
+                for field in data.fields.iter_mut() {
+                    self.filter_attrs(&mut field.attrs, layer);
+
+                    let ty = &field.ty;
+                    field.ty = syn::parse_quote!(::core::option::Option<#ty>);
+                }
+            }
+
+            input.to_tokens(&mut self.tokens);
+
+            let doc = format!(
+                "Override the fields of a [`{original}`] with the fields of this layer \
+                 that are `Some`."
+            );
+            self.tokens.extend(quote::quote! {
+                impl #impl_generics #layer #ty_generics
+                #where_clause
+                {
+                    #[doc = #doc]
+                    pub fn apply_to(self, target: &mut #original #ty_generics) {
+                        #(
+                            if let ::core::option::Option::Some(value) = self.#members {
+                                target.#members = value;
+                            }
+                        )*
+                    }
+                }
+            });
+        }
+
+        let vis = &self.input.vis;
+        let layers = &profiles.layers;
+        let args: Vec<_> = layers
+            .iter()
+            .map(|layer| syn::Ident::new(&layer.unraw().to_string().to_snake_case(), layer.span()))
+            .collect();
+        let enum_doc = format!("The configuration layers of [`{original}`].");
+        let merge_doc = format!(
+            "Merge each layer into `base`, in the order they are declared in \
+             [`{enum_name}`]."
+        );
+
+        self.tokens.extend(quote::quote! {
+            #[doc = #enum_doc]
+            #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+            #vis enum #enum_name {
+                #( #layers, )*
+            }
+
+            impl #enum_name {
+                /// All the layers, in the order they are applied.
+                pub const ALL: &'static [Self] = &[ #( Self::#layers, )* ];
+
+                #[doc = #merge_doc]
+                pub fn merge_all #impl_generics (
+                    base: #original #ty_generics,
+                    #( #args: #layers #ty_generics, )*
+                ) -> #original #ty_generics
+                #where_clause
+                {
+                    let mut base = base;
+                    #( #args.apply_to(&mut base); )*
+                    base
+                }
+            }
+        });
+    }
+
+    /// Report an error for each `#[substruct]` expression on a field or
+    /// variant that refers to an unknown struct or that doesn't match any of
+    /// the generated structs.
+    ///
+    /// Literal `false` expressions are allowed since they are clearly
+    /// intentional.
+    fn check_field_exprs(&mut self) {
+        let attrs: Vec<&syn::Attribute> = match &self.input.data {
+            syn::Data::Struct(data) => data.fields.iter().flat_map(|field| &field.attrs).collect(),
+            syn::Data::Enum(data) => data
+                .variants
+                .iter()
+                .flat_map(|variant| {
+                    variant
+                        .attrs
+                        .iter()
+                        .chain(variant.fields.iter().flat_map(|field| &field.attrs))
+                })
+                .collect(),
+            syn::Data::Union(data) => data
+                .fields
+                .named
+                .iter()
+                .flat_map(|field| &field.attrs)
+                .collect(),
+        };
+
+        let mut errors = Vec::new();
+        for attr in attrs {
+            if !attr.path().is_ident("substruct") {
+                continue;
+            }
+
+            // Parse errors are reported by `parse_field_inputs`.
+            let Ok(mut input) = attr.parse_args::<SubstructInput>() else {
+                continue;
+            };
+
+            for arg in &mut input.args {
+                errors.extend(
+                    arg.attrs
+                        .iter()
+                        .filter(|attr| attr.path().is_ident("cfg"))
+                        .map(|attr| {
+                            syn::Error::new_spanned(
+                                attr,
+                                "#[cfg] is only permitted within the struct-level #[substruct] \
+                                 attribute",
+                            )
+                        }),
+                );
+
+                let unknown: Vec<_> = arg
+                    .expr
+                    .idents()
+                    .into_iter()
+                    .filter(|ident| !self.groups.contains_key(*ident))
+                    .filter(|ident| !self.args.keys().any(|name| name.unraw() == ident.unraw()))
+                    .map(|ident| {
+                        syn::Error::new(
+                            ident.span(),
+                            format_args!(
+                                "unknown struct `{ident}`, expected one of the structs listed in \
+                                 the `#[substruct]` attribute on `{}`",
+                                self.input.ident
+                            ),
+                        )
+                    })
+                    .collect();
+                if !unknown.is_empty() {
+                    errors.extend(unknown);
+                    continue;
+                }
+
+                if matches!(arg.expr, Expr::Lit(_)) {
+                    continue;
+                }
+
+                let span = arg.expr.to_token_stream();
+                arg.expr.expand_groups(&self.groups);
+                if !self.args.keys().any(|name| arg.expr.evaluate(name)) {
+                    errors.push(syn::Error::new_spanned(
+                        span,
+                        "this expression does not match any of the generated structs",
+                    ));
+                }
+            }
+        }
+
+        self.errors.extend(errors);
+    }
+
+    /// Build a doc section for the parent struct which lists all the structs
+    /// that are generated from it.
+    fn emit_summary(&mut self) {
+        let args = self.args.clone();
+        let mut lines = vec![
+            String::new(),
+            "# Generated Structs".to_owned(),
+            "The following structs are generated from this one by `#[substruct]`:".to_owned(),
+        ];
+
+        for name in args.keys() {
+            if *name == self.input.ident {
+                continue;
+            }
+
+            let count = self.count_fields(name);
+            let plural = if count == 1 { "" } else { "s" };
+            lines.push(format!("- [`{name}`] ({count} field{plural})"));
+        }
+
+        for line in lines {
+            self.parent_attrs.push(syn::parse_quote!(#[doc = #line]));
+        }
+    }
+
+    /// Emit the shared structs for the `core = Shared` option.
+    ///
+    /// Each one contains the fields of the parent which are included in all
+    /// the generated structs using it. The fields keep the attributes of the
+    /// parent since they are shared between multiple structs.
+    fn emit_cores(&mut self) {
+        let args = self.args.clone();
+        let mut cores: IndexMap<&syn::Ident, Vec<&syn::Ident>> = IndexMap::new();
+        for (name, tla) in args.iter() {
+            if let Some(core) = &tla.core {
+                cores.entry(core).or_default().push(name);
+            }
+        }
+
+        for (core, users) in cores {
+            if args.contains_key(core) || self.groups.contains_key(core) {
+                self.errors.push(syn::Error::new(
+                    core.span(),
+                    format_args!("`{core}` is already the name of a struct or group"),
+                ));
+                continue;
+            }
+
+            let fields = self.core_fields(core);
+            for field in &fields {
+                for name in &users {
+                    let input = self.field_input(&field.attrs);
+                    let has_options = input
+                        .matching(name)
+                        .is_some_and(|arg| !arg.options.is_empty());
+
+                    if has_options || self.field_rename(field, name).is_some() {
+                        self.errors.push(syn::Error::new_spanned(
+                            field,
+                            format_args!(
+                                "fields shared through `{core}` cannot have field options or \
+                                 be renamed"
+                            ),
+                        ));
+                        break;
+                    }
+                }
+            }
+
+            let mut input = self.input.clone();
+            input.ident = core.clone();
+            input
+                .attrs
+                .retain(|attr| !is_doc_comment(attr) && !attr.path().is_ident("non_exhaustive"));
+            self.filter_attrs(&mut input.attrs, core);
+
+            let users: Vec<_> = users.iter().map(|name| format!("[`{name}`]")).collect();
+            let doc = format!("The fields shared by {}.", users.join(", "));
+            input.attrs.insert(0, syn::parse_quote!(#[doc = #doc]));
+
+            let fields = syn::Fields::Named(syn::FieldsNamed {
+                brace_token: Default::default(),
+                named: fields
+                    .into_iter()
+                    .map(|mut field| {
+                        self.filter_attrs(&mut field.attrs, core);
+                        field
+                    })
+                    .collect(),
+            });
+            trim_generics(&mut input.generics, &fields);
+            input.data = syn::Data::Struct(syn::DataStruct {
+                struct_token: Default::default(),
+                fields,
+                semi_token: None,
+            });
+
+            input.to_tokens(&mut self.tokens);
+        }
+    }
+
+    /// Get the fields of the parent which are included in every generated
+    /// struct using `core`.
+    fn core_fields(&mut self, core: &syn::Ident) -> Vec<syn::Field> {
+        let users: Vec<_> = self
+            .args
+            .iter()
+            .filter(|(_, tla)| tla.core.as_ref() == Some(core))
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        let fields = match &self.input.data {
+            syn::Data::Struct(syn::DataStruct {
+                fields: syn::Fields::Named(fields),
+                ..
+            }) => fields.named.clone(),
+            _ => return Vec::new(),
+        };
+
+        fields
+            .into_iter()
+            .filter(|field| {
+                let input = self.field_input(&field.attrs);
+                !input.exclusive() && users.iter().all(|name| input.matching(name).is_some())
+            })
+            .collect()
+    }
+
+    /// Get the generics of the `core` struct, which only keeps the generic
+    /// parameters of the parent used by its fields.
+    fn core_generics(&mut self, core: &syn::Ident) -> syn::Generics {
+        let fields = syn::Fields::Named(syn::FieldsNamed {
+            brace_token: Default::default(),
+            named: self.core_fields(core).into_iter().collect(),
+        });
+
+        let mut generics = self.input.generics.clone();
+        trim_generics(&mut generics, &fields);
+        generics
+    }
+
+    /// Emit the module for the `meta` option.
+    fn emit_meta(&mut self, module: &syn::Ident) {
+        let args = self.args.clone();
+        let original = &self.input.ident;
+        let vis = &self.input.vis;
+        let child_count = args.len() - 1;
+        let child_doc =
+            format!("The number of structs generated from [`{original}`](super::{original}).");
+
+        // Enums have variants instead of fields so there is nothing to count.
+        let mut counts = Vec::new();
+        if !matches!(self.input.data, syn::Data::Enum(_)) {
+            for (name, tla) in args.iter() {
+                let count = self.count_fields(name) + tla.extras.len();
+                let ident = quote::format_ident!(
+                    "{}_FIELDS",
+                    name.unraw().to_string().to_shouty_snake_case()
+                );
+                let doc = format!("The number of fields in [`{name}`](super::{name}).");
+
+                counts.push(quote::quote! {
+                    #[doc = #doc]
+                    pub const #ident: usize = #count;
+                });
+            }
+        }
+
+        let doc = format!("Constants describing the structs generated from [`{original}`].");
+        self.tokens.extend(quote::quote! {
+            #[doc = #doc]
+            #[allow(dead_code)]
+            #vis mod #module {
+                #[doc = #child_doc]
+                pub const CHILD_COUNT: usize = #child_count;
+
+                #( #counts )*
+            }
+        });
+    }
+
+    /// Count the number of fields that will be emitted within the struct
+    /// `name`.
+    fn count_fields(&mut self, name: &syn::Ident) -> usize {
+        let fields: Vec<_> = match &self.input.data {
+            syn::Data::Struct(data) => data.fields.iter().cloned().collect(),
+            syn::Data::Union(data) => data.fields.named.iter().cloned().collect(),
+            syn::Data::Enum(_) => Vec::new(),
+        };
+
+        let mut count = 0;
+        for mut field in fields {
+            let split = self.split_field(&field, name);
+            if !self.filter_field(&mut field, name) {
+                continue;
+            }
+
+            count += match split {
+                Some(split) => split.len(),
+                None => 1,
+            };
+        }
+
+        count
+    }
+
+    fn emit_struct(&mut self, name: &syn::Ident) {
+        let args = self.args.clone();
+        let tla = match args.get(name) {
+            Some(tla) => tla,
+            None => panic!("Attempted to emit struct `{name}` with no corresponding entry in the top-level arguments")
+        };
+
+        let mut input = self.input.clone();
+        input.ident = name.clone();
+
+        if let Some(vis) = &tla.vis {
+            input.vis = vis.clone();
+        }
+
+        if let Some((option, order)) = &tla.generic_order {
+            let result = match input.ident == self.input.ident {
+                true => Err(syn::Error::new(
+                    option.span(),
+                    "generic_order cannot be used on the parent struct",
+                )),
+                false => reorder_generics(&mut input.generics, option, order),
+            };
+
+            if let Err(e) = result {
+                self.errors.push(e);
+            }
+        }
+
+        if !tla.where_predicates.is_empty() {
+            input
+                .generics
+                .make_where_clause()
+                .predicates
+                .extend(tla.where_predicates.iter().cloned());
+        }
+
+        if let Some(allowed) = &self.options.propagate_derives {
+            if input.ident != self.input.ident {
+                let result = retain_derives(&mut input.attrs, |path| {
+                    allowed
+                        .iter()
+                        .any(|allowed| is_derive(path, &derive_name(allowed)))
+                });
+
+                if let Err(e) = result {
+                    self.errors.push(e);
+                }
+            }
+        }
+
+        // `#[non_exhaustive]` is not copied onto generated structs since it
+        // prevents other crates from constructing them. It can still be added
+        // with `#[substruct_attr]`.
+        if input.ident != self.input.ident {
+            input
+                .attrs
+                .retain(|attr| !attr.path().is_ident("non_exhaustive"));
+        }
+
+        // This needs to come before the doc override so that `substruct_nodoc`
+        // doesn't remove it.
+        self.filter_attrs(&mut input.attrs, name);
+
+        if !tla.docs.is_empty() {
+            input.attrs.retain(|attr| !is_doc_comment(attr));
+            input.attrs.extend(interpolate_docs(&tla.docs, name));
+        }
+
+        if input.ident == self.input.ident {
+            input.attrs.extend_from_slice(&self.parent_attrs);
+        }
+
+        input
+            .attrs
+            .extend(tla.attrs.iter().map(|meta| syn::parse_quote!(#[#meta])));
+        input.attrs.extend(tla.arg_attrs.iter().cloned());
+
+        if input.ident == self.input.ident && !cfg_attrs(&tla.arg_attrs).is_empty() {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "#[cfg] cannot be used on the parent struct within #[substruct]",
+            ));
+        }
+
+        // The derives need to come before any helper attributes they use.
+        if !tla.derives.is_empty() {
+            let derives = &tla.derives;
+            input
+                .attrs
+                .insert(0, syn::parse_quote!(#[derive(#( #derives ),*)]));
+        }
+
+        if let Some(repr) = &tla.repr {
+            input.attrs.retain(|attr| !attr.path().is_ident("repr"));
+            input.attrs.push(syn::parse_quote!(#[repr(#repr)]));
+        }
+
+        if tla.eq_with.is_some() {
+            let result = retain_derives(&mut input.attrs, |path| !is_derive(path, "PartialEq"));
+
+            if let Err(e) = result {
+                self.errors.push(e);
+            }
+        }
+
+        if tla.derive_default && !has_derive(&input.attrs, "Default") {
+            input
+                .attrs
+                .push(syn::parse_quote!(#[derive(::core::default::Default)]));
+        }
+
+        if tla.keep_markers && input.ident != self.input.ident {
+            let marker: syn::Attribute = syn::parse_quote!(#[substruct_emitted(#name)]);
+            let markers: Vec<&mut Vec<syn::Attribute>> = match &mut input.data {
+                syn::Data::Struct(data) => data
+                    .fields
+                    .iter_mut()
+                    .map(|field| &mut field.attrs)
+                    .collect(),
+                syn::Data::Union(data) => data
+                    .fields
+                    .named
+                    .iter_mut()
+                    .map(|field| &mut field.attrs)
+                    .collect(),
+                syn::Data::Enum(data) => data
+                    .variants
+                    .iter_mut()
+                    .map(|variant| &mut variant.attrs)
+                    .collect(),
+            };
+
+            // Fields and variants which are filtered out lose the marker along
+            // with the rest of their attributes.
+            for attrs in markers {
+                attrs.push(marker.clone());
+            }
+
+            let krate = match &self.options.crate_path {
+                Some(path) => path.clone(),
+                None => syn::parse_quote!(::substruct),
+            };
+
+            input.attrs.push(marker);
+            input
+                .attrs
+                .insert(0, syn::parse_quote!(#[derive(#krate::SubstructEmitted)]));
+        }
+
+        let core = match &tla.core {
+            Some(_) if input.ident == self.input.ident => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "`core` cannot be used on the parent struct",
+                ));
+                None
+            }
+            Some(_)
+                if tla.borrow
+                    || tla.optional
+                    || tla.trim_generics
+                    || tla.named.is_some()
+                    || tla.merges.is_some()
+                    || tla.parent.is_some()
+                    || tla.eq_with.is_some()
+                    || tla.debug_entries
+                    || tla.sibling_from
+                    || tla.assert_subset
+                    || !tla.chain_to.is_empty() =>
+            {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "`core` cannot be combined with `borrow`, `optional`, `trim_generics`, \
+                     `named`, `merges`, `parent`, `eq_with`, `debug_entries`, `sibling_from`, \
+                     `assert_subset`, or `chain_to`",
+                ));
+                None
+            }
+            Some(core) => Some(core.clone()),
+            None => None,
+        };
+
+        // The fields shared through the core are replaced with a single field
+        // containing the core.
+        if let Some(core) = &core {
+            let shared: Vec<_> = self
+                .core_fields(core)
+                .into_iter()
+                .filter_map(|field| field.ident)
+                .collect();
+
+            match &mut input.data {
+                syn::Data::Struct(syn::DataStruct {
+                    fields: syn::Fields::Named(fields),
+                    ..
+                }) => {
+                    fields.named = std::mem::take(&mut fields.named)
+                        .into_pairs()
+                        .filter(|pair| {
+                            pair.value()
+                                .ident
+                                .as_ref()
+                                .is_none_or(|ident| !shared.contains(ident))
+                        })
+                        .collect();
+                }
+                _ => self.errors.push(syn::Error::new(
+                    name.span(),
+                    "`core` can only be used on structs with named fields",
+                )),
+            }
+        }
+
+        match &mut input.data {
+            syn::Data::Enum(data) => self.filter_variants(data, name),
+            syn::Data::Struct(data) => match &mut data.fields {
+                syn::Fields::Named(fields) => self.filter_fields_named(fields, name),
+                syn::Fields::Unnamed(fields) => self.filter_fields_unnamed(fields, name),
+                syn::Fields::Unit => (),
+            },
+            syn::Data::Union(data) => self.filter_fields_named(&mut data.fields, name),
+        };
+
+        if let Some(names) = &tla.named {
+            match &mut input.data {
+                syn::Data::Struct(_) if input.ident == self.input.ident => {
+                    self.errors.push(syn::Error::new(
+                        name.span(),
+                        "`named` cannot be used on the parent struct",
+                    ));
+                }
+                syn::Data::Struct(data) => match &data.fields {
+                    syn::Fields::Unnamed(fields) if fields.unnamed.len() == names.len() => {
+                        let named = fields
+                            .unnamed
+                            .iter()
+                            .zip(names)
+                            .map(|(field, ident)| syn::Field {
+                                ident: Some(ident.clone()),
+                                colon_token: Some(Default::default()),
+                                ..field.clone()
+                            })
+                            .collect();
+
+                        data.fields = syn::Fields::Named(syn::FieldsNamed {
+                            brace_token: Default::default(),
+                            named,
+                        });
+                        data.semi_token = None;
+                    }
+                    syn::Fields::Unnamed(fields) => self.errors.push(syn::Error::new(
+                        name.span(),
+                        format_args!(
+                            "`named` lists {} names but `{name}` has {} fields",
+                            names.len(),
+                            fields.unnamed.len()
+                        ),
+                    )),
+                    _ => self.errors.push(syn::Error::new(
+                        name.span(),
+                        "`named` can only be used when the parent is a tuple struct",
+                    )),
+                },
+                _ => self.errors.push(syn::Error::new(
+                    name.span(),
+                    "`named` can only be used when the parent is a tuple struct",
+                )),
+            }
+        }
+
+        if let (
+            Some(core),
+            syn::Data::Struct(syn::DataStruct {
+                fields: syn::Fields::Named(fields),
+                ..
+            }),
+        ) = (&core, &mut input.data)
+        {
+            let vis = &input.vis;
+            let member = core_member(core);
+            let core_generics = self.core_generics(core);
+            let (_, core_ty_generics, _) = core_generics.split_for_impl();
+            fields
+                .named
+                .insert(0, syn::parse_quote!(#vis #member: #core #core_ty_generics));
+        }
+
+        if !tla.extras.is_empty() {
+            match &mut input.data {
+                syn::Data::Struct(syn::DataStruct {
+                    fields: syn::Fields::Named(fields),
+                    ..
+                }) if input.ident != self.input.ident => {
+                    let vis = &input.vis;
+                    for (ident, ty) in &tla.extras {
+                        if fields
+                            .named
+                            .iter()
+                            .any(|field| field.ident.as_ref() == Some(ident))
+                        {
+                            self.errors.push(syn::Error::new(
+                                ident.span(),
+                                format_args!(
+                                    "field `{ident}` is defined multiple times in `{name}`"
+                                ),
+                            ));
+                            continue;
+                        }
+
+                        fields.named.push(syn::parse_quote!(#vis #ident: #ty));
+                    }
+                }
+                _ => self.errors.push(syn::Error::new(
+                    name.span(),
+                    "extra can only be used on generated structs with named fields",
+                )),
+            }
+        }
+
+        if tla.borrow {
+            match &mut input.data {
+                syn::Data::Struct(data) => {
+                    input.generics.params.insert(0, syn::parse_quote!('borrow));
+
+                    for field in data.fields.iter_mut() {
+                        let ty = &field.ty;
+                        field.ty = syn::parse_quote!(&'borrow #ty);
+                    }
+                }
+                _ => self.errors.push(syn::Error::new(
+                    name.span(),
+                    "borrow can only be used on structs",
+                )),
+            }
+        }
+
+        if tla.trim_generics && input.ident != self.input.ident {
+            match &input.data {
+                syn::Data::Struct(data) => trim_generics(&mut input.generics, &data.fields),
+                _ => self.errors.push(syn::Error::new(
+                    name.span(),
+                    "trim_generics can only be used on structs",
+                )),
+            }
+        }
+
+        if tla.optional {
+            match &mut input.data {
+                syn::Data::Struct(_) if tla.borrow => self.errors.push(syn::Error::new(
+                    name.span(),
+                    "optional cannot be combined with borrow",
+                )),
+                syn::Data::Struct(data) => {
+                    // Unset fields are left out when serializing so that the
+                    // output only contains the fields that were provided.
+                    let serialize = has_derive(&input.attrs, "Serialize");
+
+                    for field in data.fields.iter_mut() {
+                        let ty = &field.ty;
+                        field.ty = syn::parse_quote!(::core::option::Option<#ty>);
+
+                        if serialize {
+                            field.attrs.push(syn::parse_quote!(
+                                #[serde(skip_serializing_if = "::core::option::Option::is_none")]
+                            ));
+                        }
+                    }
+                }
+                _ => self.errors.push(syn::Error::new(
+                    name.span(),
+                    "optional can only be used on structs",
+                )),
+            }
+        }
+
+        // These need to come before the `PhantomData` fields are added.
+        if tla.field_names {
+            self.emit_field_names(&input);
+        }
+
+        if tla.getters {
+            self.emit_getters(&input);
+        }
+
+        if tla.iter {
+            self.emit_iter(&input);
+        }
+
+        if tla.phantom && input.ident != self.input.ident {
+            match &mut input.data {
+                syn::Data::Struct(_) if tla.borrow || tla.optional => {
+                    self.errors.push(syn::Error::new(
+                        name.span(),
+                        "phantom cannot be combined with borrow or optional",
+                    ))
+                }
+                syn::Data::Struct(data) => {
+                    add_phantom_fields(&input.generics, &input.vis, &mut data.fields)
+                }
+                _ => self.errors.push(syn::Error::new(
+                    name.span(),
+                    "phantom can only be used on structs",
+                )),
+            }
+        }
+
+        input.to_tokens(&mut self.tokens);
+        let item_attrs = emitted_attrs(&input);
+
+        if let Some(core) = &core {
+            let member = core_member(core);
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            let core_generics = self.core_generics(core);
+            let (_, core_ty_generics, _) = core_generics.split_for_impl();
+
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #impl_generics ::core::ops::Deref for #name #ty_generics
+                #where_clause
+                {
+                    type Target = #core #core_ty_generics;
+
+                    fn deref(&self) -> &Self::Target {
+                        &self.#member
+                    }
+                }
+
+                #item_attrs
+                impl #impl_generics ::core::ops::DerefMut for #name #ty_generics
+                #where_clause
+                {
+                    fn deref_mut(&mut self) -> &mut Self::Target {
+                        &mut self.#member
+                    }
+                }
+            });
+        }
+
+        if let Some(eq_with) = &tla.eq_with {
+            self.emit_partial_eq(&input, eq_with);
+        }
+
+        if let Some(max_size) = &tla.max_size {
+            self.emit_size_assertion(&input, max_size);
+        }
+
+        if input.ident != self.input.ident {
+            if tla.constructor {
+                self.emit_constructor(&input);
+            }
+
+            if tla.builder {
+                self.emit_builder(&input);
+            }
+
+            if tla.cow_enum {
+                self.emit_cow_enum(&input);
+            }
+
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            for marker in &tla.markers {
+                self.tokens.extend(quote::quote! {
+                    #item_attrs
+                    impl #impl_generics #marker for #name #ty_generics
+                    #where_clause
+                    {}
+                });
+            }
+
+            if tla.skip_conversions {
+                // These options only change the conversions, which would
+                // otherwise be silently dropped.
+                let conflicts = [
+                    ("method", tla.method.is_some()),
+                    ("method_alias", !tla.method_aliases.is_empty()),
+                    ("conversion_doc", tla.conversion_doc.is_some()),
+                    ("method_attr", !tla.method_attrs.is_empty()),
+                    ("validate_parent", tla.validate_parent.is_some()),
+                    ("validate_error", tla.validate_error.is_some()),
+                    ("checked", tla.checked),
+                    ("chain_to", !tla.chain_to.is_empty()),
+                    ("partial_eq_parent", tla.partial_eq_parent),
+                    ("fill_default", tla.fill_default),
+                    ("no_reverse_from", tla.no_reverse_from),
+                    ("rest_default", tla.rest_default),
+                    ("merges", tla.merges.is_some()),
+                    ("parent", tla.parent.is_some()),
+                    ("ref", tla.by_ref),
+                    ("const", tla.const_fn),
+                    ("ref_args", tla.ref_args),
+                    ("into_args", tla.into_args),
+                    ("roundtrip", tla.roundtrip),
+                    ("from_ref", tla.from_ref),
+                    ("sibling_from", tla.sibling_from),
+                    ("assert_subset", tla.assert_subset),
+                    (
+                        "debug_assert_on_extract",
+                        tla.debug_assert_on_extract.is_some(),
+                    ),
+                ];
+
+                for (option, _) in conflicts.iter().filter(|(_, set)| *set) {
+                    self.errors.push(syn::Error::new(
+                        name.span(),
+                        format_args!("skip_conversions cannot be combined with `{option}`"),
+                    ));
+                }
+
+                return;
+            }
+
+            self.emit_conversions(&input);
+
+            if let Some(target) = &tla.parent {
+                self.emit_parent_conversions(&input, target);
+            }
+
+            if tla.sibling_from {
+                self.emit_sibling_conversions(&input);
+            }
+        }
+    }
+
+    /// Emit a `FIELD_NAMES` constant listing the names of the struct's fields.
+    fn emit_field_names(&mut self, input: &syn::DeriveInput) {
+        let item_attrs = emitted_attrs(input);
+        let name = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let fields = match &input.data {
+            syn::Data::Struct(data) => &data.fields,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "field_names can only be used on structs",
+                ));
+                return;
+            }
+        };
+
+        let names = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| match &field.ident {
+                Some(ident) => ident.unraw().to_string(),
+                None => index.to_string(),
+            });
+        let doc = format!("The names of the fields of [`{name}`], in declaration order.");
+
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            impl #impl_generics #name #ty_generics
+            #where_clause
+            {
+                #[doc = #doc]
+                pub const FIELD_NAMES: &'static [&'static str] = &[ #( #names ),* ];
+            }
+        });
+    }
+
+    /// Emit a method returning a reference to each of the struct's fields.
+    fn emit_getters(&mut self, input: &syn::DeriveInput) {
+        let item_attrs = emitted_attrs(input);
+        let name = &input.ident;
+        let vis = &input.vis;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let fields = match &input.data {
+            syn::Data::Struct(syn::DataStruct {
+                fields: syn::Fields::Named(fields),
+                ..
+            }) => &fields.named,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "getters can only be used on structs with named fields",
+                ));
+                return;
+            }
+        };
+
+        let idents: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+        let types = fields.iter().map(|field| &field.ty);
+        let cfgs = fields.iter().map(|field| cfg_attrs(&field.attrs));
+        let docs = fields
+            .iter()
+            .filter_map(|field| field.ident.as_ref())
+            .map(|ident| format!("Get a reference to the `{}` field.", ident.unraw()));
+
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            impl #impl_generics #name #ty_generics
+            #where_clause
+            {
+                #(
+                    #[doc = #docs]
+                    #( #cfgs )*
+                    #vis fn #idents(&self) -> &#types {
+                        &self.#idents
+                    }
+                )*
+            }
+        });
+    }
+
+    /// Emit an `iter` method and an `IntoIterator` impl which iterate over the
+    /// struct's fields in order.
+    fn emit_iter(&mut self, input: &syn::DeriveInput) {
+        let item_attrs = emitted_attrs(input);
+        let name = &input.ident;
+        let vis = &input.vis;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let fields = match &input.data {
+            syn::Data::Struct(data) if !data.fields.is_empty() => &data.fields,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "iter can only be used on structs with at least one field",
+                ));
+                return;
+            }
+        };
+
+        let ty = &fields.iter().next().unwrap().ty;
+        let expected = ty.to_token_stream().to_string();
+        let mut valid = true;
+        for field in fields.iter().skip(1) {
+            if field.ty.to_token_stream().to_string() != expected {
+                valid = false;
+                self.errors.push(syn::Error::new_spanned(
+                    &field.ty,
+                    format_args!(
+                        "iter requires all fields of `{name}` to have the same type `{}`",
+                        ty.to_token_stream()
+                    ),
+                ));
+            }
+        }
+
+        if let Some(field) = fields
+            .iter()
+            .find(|field| !cfg_attrs(&field.attrs).is_empty())
+        {
+            valid = false;
+            self.errors.push(syn::Error::new_spanned(
+                field,
+                "iter cannot be used on structs with `#[cfg]` fields",
+            ));
+        }
+
+        if !valid {
+            return;
+        }
+
+        let members: Vec<syn::Member> = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| match &field.ident {
+                Some(ident) => syn::Member::Named(ident.clone()),
+                None => syn::Member::Unnamed(index.into()),
+            })
+            .collect();
+        let len = members.len();
+        let doc = format!("Iterate over references to the fields of [`{name}`], in order.");
+
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            impl #impl_generics #name #ty_generics
+            #where_clause
+            {
+                #[doc = #doc]
+                #vis fn iter(&self) -> impl ::core::iter::Iterator<Item = &#ty> {
+                    ::core::iter::IntoIterator::into_iter([ #( &self.#members, )* ])
+                }
+            }
+
+            #item_attrs
+            impl #impl_generics ::core::iter::IntoIterator for #name #ty_generics
+            #where_clause
+            {
+                type Item = #ty;
+                type IntoIter = ::core::array::IntoIter<#ty, #len>;
+
+                fn into_iter(self) -> Self::IntoIter {
+                    ::core::iter::IntoIterator::into_iter([ #( self.#members, )* ])
+                }
+            }
+        });
+    }
+
+    /// Emit a `<Child>Builder` struct with a setter for each of the struct's
+    /// fields.
+    fn emit_builder(&mut self, input: &syn::DeriveInput) {
+        let item_attrs = emitted_attrs(input);
+        let name = &input.ident;
+        let vis = &input.vis;
+        let builder = quote::format_ident!("{}Builder", name);
+        let generics = &input.generics;
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let fields = match &input.data {
+            syn::Data::Struct(syn::DataStruct {
+                fields: syn::Fields::Named(fields),
+                ..
+            }) => &fields.named,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "builder can only be used on structs with named fields",
+                ));
+                return;
+            }
+        };
+
+        let idents: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+        let types: Vec<_> = fields.iter().map(|field| &field.ty).collect();
+        let missing: Vec<_> = fields
+            .iter()
+            .filter_map(|field| field.ident.as_ref())
+            .map(|ident| ident.unraw().to_string())
+            .collect();
+        let struct_doc = format!("A builder for [`{name}`].");
+        let builder_doc = format!("Create a [`{builder}`] with none of its fields set.");
+        let setter_docs = missing
+            .iter()
+            .map(|field| format!("Set the value of the `{field}` field."));
+        let build_doc = format!(
+            "Build the [`{name}`].\n\nIf any fields have not been set then the name of the \
+             first missing field is returned as the error."
+        );
+
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            #[doc = #struct_doc]
+            #vis struct #builder #generics
+            #where_clause
+            {
+                #( #idents: ::core::option::Option<#types>, )*
+            }
+
+            #item_attrs
+            impl #impl_generics ::core::default::Default for #builder #ty_generics
+            #where_clause
+            {
+                fn default() -> Self {
+                    Self {
+                        #( #idents: ::core::option::Option::None, )*
+                    }
+                }
+            }
+
+            #item_attrs
+            impl #impl_generics #name #ty_generics
+            #where_clause
+            {
+                #[doc = #builder_doc]
+                #vis fn builder() -> #builder #ty_generics {
+                    ::core::default::Default::default()
+                }
+            }
+
+            #item_attrs
+            impl #impl_generics #builder #ty_generics
+            #where_clause
+            {
+                #(
+                    #[doc = #setter_docs]
+                    #vis fn #idents(mut self, value: #types) -> Self {
+                        self.#idents = ::core::option::Option::Some(value);
+                        self
+                    }
+                )*
+
+                #[doc = #build_doc]
+                #vis fn build(self) -> ::core::result::Result<#name #ty_generics, &'static str> {
+                    ::core::result::Result::Ok(#name {
+                        #( #idents: self.#idents.ok_or(#missing)?, )*
+                    })
+                }
+            }
+        });
+    }
+
+    /// Emit a `new` constructor which takes each of the struct's fields in
+    /// order.
+    ///
+    /// Only one field of a union can be initialized at a time, so unions get a
+    /// `new_<field>` constructor for each of their fields instead.
+    fn emit_constructor(&mut self, input: &syn::DeriveInput) {
+        let item_attrs = emitted_attrs(input);
+        let name = &input.ident;
+        let vis = &input.vis;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let fields = match &input.data {
+            syn::Data::Struct(data) => &data.fields,
+            syn::Data::Union(data) => {
+                let methods = data.fields.named.iter().map(|field| {
+                    let ident = field.ident.as_ref().unwrap();
+                    let ty = &field.ty;
+                    let method = quote::format_ident!("new_{}", ident.unraw());
+                    let doc = format!(
+                        "Create a new [`{name}`] with the `{}` field active.",
+                        ident.unraw()
+                    );
+
+                    quote::quote! {
+                        #[doc = #doc]
+                        #vis fn #method(#ident: #ty) -> Self {
+                            Self { #ident }
+                        }
+                    }
+                });
+
+                self.tokens.extend(quote::quote! {
+                    #item_attrs
+                    impl #impl_generics #name #ty_generics
+                    #where_clause
+                    {
+                        #( #methods )*
+                    }
+                });
+                return;
+            }
+            _ => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "constructor can only be used on structs and unions",
+                ));
+                return;
+            }
+        };
+
+        let args: Vec<_> = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| match &field.ident {
+                Some(ident) => IdentOrIndex::Ident(ident.clone()),
+                None => IdentOrIndex::Index(index),
+            })
+            .map(IdentOrIndex::into_ident)
+            .collect();
+        let types = fields.iter().map(|field| &field.ty);
+
+        let body = match fields {
+            syn::Fields::Named(_) => quote::quote!(Self { #( #args, )* }),
+            syn::Fields::Unnamed(_) => quote::quote!(Self( #( #args, )* )),
+            syn::Fields::Unit => quote::quote!(Self),
+        };
+        let doc = format!("Create a new [`{name}`].");
+
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            impl #impl_generics #name #ty_generics
+            #where_clause
+            {
+                #[doc = #doc]
+                #vis fn new(#( #args: #types, )*) -> Self {
+                    #body
+                }
+            }
+        });
+    }
+
+    /// Emit a `MaybeOwned<Child>` enum which holds either an owned or a
+    /// borrowed instance of the struct, along with accessors for each of its
+    /// fields.
+    fn emit_cow_enum(&mut self, input: &syn::DeriveInput) {
+        let item_attrs = emitted_attrs(input);
+        let name = &input.ident;
+        let vis = &input.vis;
+        let enum_name = syn::Ident::new(&format!("MaybeOwned{name}"), name.span());
+        let (_, ty_generics, _) = input.generics.split_for_impl();
+
+        let fields = match &input.data {
+            syn::Data::Struct(syn::DataStruct {
+                fields: syn::Fields::Named(fields),
+                ..
+            }) => &fields.named,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "cow_enum can only be used on structs with named fields",
+                ));
+                return;
+            }
+        };
+
+        let mut generics = input.generics.clone();
+        generics.params.insert(0, syn::parse_quote!('borrow));
+        let (impl_generics, enum_generics, where_clause) = generics.split_for_impl();
+
+        let accessors = fields.iter().map(|field| {
+            let ident = &field.ident;
+            let ty = &field.ty;
+            let doc = format!(
+                "Get a reference to the `{}` field.",
+                ident.as_ref().unwrap()
+            );
+
+            quote::quote! {
+                #[doc = #doc]
+                #vis fn #ident(&self) -> &#ty {
+                    &self.get().#ident
+                }
+            }
+        });
+
+        let doc = format!("Either an owned or a borrowed [`{name}`].");
+        let get_doc = format!("Get a reference to the contained [`{name}`].");
+
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            #[doc = #doc]
+            #vis enum #enum_name #generics
+            #where_clause
+            {
+                Owned(#name #ty_generics),
+                Borrowed(&'borrow #name #ty_generics),
+            }
+
+            #item_attrs
+            impl #impl_generics #enum_name #enum_generics
+            #where_clause
+            {
+                #[doc = #get_doc]
+                #vis fn get(&self) -> &#name #ty_generics {
+                    match self {
+                        Self::Owned(value) => value,
+                        Self::Borrowed(value) => value,
+                    }
+                }
+
+                #( #accessors )*
+            }
+
+            #item_attrs
+            impl #impl_generics ::core::convert::From<#name #ty_generics> for #enum_name #enum_generics
+            #where_clause
+            {
+                fn from(value: #name #ty_generics) -> Self {
+                    Self::Owned(value)
+                }
+            }
+
+            #item_attrs
+            impl #impl_generics ::core::convert::From<&'borrow #name #ty_generics> for #enum_name #enum_generics
+            #where_clause
+            {
+                fn from(value: &'borrow #name #ty_generics) -> Self {
+                    Self::Borrowed(value)
+                }
+            }
+        });
+    }
+
+    /// Emit a `PartialEq` impl which uses custom comparison functions for some
+    /// of the fields.
+    fn emit_partial_eq(&mut self, input: &syn::DeriveInput, eq_with: &[EqWith]) {
+        let item_attrs = emitted_attrs(input);
+        let name = &input.ident;
+        let fields = match &input.data {
+            syn::Data::Struct(data) => &data.fields,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "eq_with can only be used on structs",
+                ));
+                return;
+            }
+        };
+
+        let members: Vec<syn::Member> = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| match &field.ident {
+                Some(ident) => syn::Member::Named(ident.clone()),
+                None => syn::Member::Unnamed(index.into()),
+            })
+            .collect();
+        for eq in eq_with {
+            if !members.contains(&eq.member) {
+                self.errors.push(syn::Error::new_spanned(
+                    &eq.member,
+                    format_args!("`{name}` has no such field"),
+                ));
+            }
+        }
+
+        let mut comparisons = Vec::with_capacity(members.len());
+        for member in &members {
+            let eq = eq_with.iter().find(|eq| eq.member == *member);
+            comparisons.push(match eq.map(|eq| eq.func.parse::<syn::Path>()) {
+                Some(Ok(func)) => quote::quote!(#func(&self.#member, &other.#member)),
+                Some(Err(e)) => {
+                    self.errors.push(e);
+                    return;
+                }
+                None => quote::quote!(self.#member == other.#member),
+            });
+        }
+
+        let mut generics = input.generics.clone();
+        let params: Vec<_> = generics
+            .type_params()
+            .map(|param| param.ident.clone())
+            .collect();
+        let where_clause = generics.make_where_clause();
+        for param in params {
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#param: ::core::cmp::PartialEq));
+        }
+
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            impl #impl_generics ::core::cmp::PartialEq for #name #ty_generics
+            #where_clause
+            {
+                fn eq(&self, other: &Self) -> bool {
+                    true #( && #comparisons )*
+                }
+            }
+        });
+    }
+
+    fn emit_conversions(&mut self, substruct: &syn::DeriveInput) {
+        let item_attrs = emitted_attrs(substruct);
+        if !self.errors.is_empty() {
+            return;
+        }
+
+        let original = &self.input.ident;
+        let name = &substruct.ident;
+        let (impl_generics, ty_generics, where_clause) = substruct.generics.split_for_impl();
+        // The child may have reordered generic parameters so the parent needs to
+        // use its own order.
+        let (_, parent_generics, _) = self.input.generics.split_for_impl();
+
+        let top_level = self.args.clone();
+        let tla = &top_level[name];
+        let method = tla.method_name(original);
+        let aliases = &tla.method_aliases;
+        let method_attrs = &tla.method_attrs;
+        let doc = tla.conversion_doc(original);
+
+        if tla.assert_subset && !matches!(self.input.data, syn::Data::Struct(_)) {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "`assert_subset` can only be used on structs",
+            ));
+            return;
+        }
+
+        let fields = match &self.input.data {
+            syn::Data::Enum(data) => {
+                self.emit_enum_conversions(substruct, data);
+                return;
+            }
+            // Reading a field of a union requires knowing which field is
+            // active, which can't be determined from the union itself. There
+            // is no way to convert between unions without `unsafe`, so they
+            // get no conversions.
+            syn::Data::Union(_) => return,
+            // Unit structs have no fields and so they have no conversions
+            syn::Data::Struct(data) if matches!(data.fields, syn::Fields::Unit) => return,
+            syn::Data::Struct(data) => &data.fields,
+        };
+
+        let mut included = IndexMap::new();
+        let mut excluded = IndexMap::new();
+        // Excluded fields which are filled in with `Default::default()` or the
+        // value from their `#[substruct_default]`.
+        let mut defaulted = IndexMap::new();
+        // Bounds required by `into_<parent>` and by `From<Parent>`,
+        // respectively.
+        let mut into_bounds: Vec<syn::WherePredicate> = Vec::new();
+        let mut from_bounds: Vec<syn::WherePredicate> = Vec::new();
+        // Bounds required by `partial_eq_parent`.
+        let mut eq_bounds: Vec<syn::WherePredicate> = Vec::new();
+        // The variants of the conversion error for fields using `via`, along
+        // with the error type of each.
+        let mut try_into_errors = Vec::new();
+        // The `#[cfg]` attributes on each field of the parent, which need to be
+        // applied to every place the field is referenced.
+        let mut cfgs = IndexMap::new();
+        // Fields of the struct which are not present in the parent.
+        let mut exclusive = Vec::new();
+        // The names given to the included fields of a tuple parent by `named`.
+        let mut names = tla.named.iter().flatten();
+
+        for (index, mut field) in fields.iter().cloned().enumerate() {
+            let is_exclusive = self.field_input(&field.attrs).exclusive();
+            let from_fn = self.field_str_option(&field, &substruct.ident, "from_fn");
+            let with = self.field_str_option(&field, &substruct.ident, "with");
+            let into = self.field_has_flag(&field, &substruct.ident, "into");
+            let map_into = self.field_has_flag(&field, &substruct.ident, "map_into");
+            let split = self.field_list_option(&field, &substruct.ident, "split");
+            let narrowed = self.narrowed(&field, &substruct.ident).is_some();
+            let via: Option<syn::Ident> = self
+                .field_str_option(&field, &substruct.ident, "via")
+                .or_else(|| narrowed.then(|| syn::Ident::new("try_into", Span::call_site())));
+            let default_value = self.field_default(&field, &substruct.ident);
+            let parent_ty = field.ty.clone();
+            let parent_ident = field.ident.clone();
+            let filter = self.filter_field(&mut field, &substruct.ident);
+            let default = !filter && self.field_has_flag(&field, &substruct.ident, "default");
+            let id = match parent_ident {
+                Some(ident) => IdentOrIndex::Ident(ident),
+                None => IdentOrIndex::Index(index),
+            };
+            let cfg = cfg_attrs(&field.attrs);
+            cfgs.insert(id.clone(), quote::quote!(#( #cfg )*));
+
+            if is_exclusive {
+                if field.ident.is_none() {
+                    self.errors.push(syn::Error::new_spanned(
+                        &field,
+                        "`exclusive` cannot be used on the fields of a tuple struct",
+                    ));
+                } else if filter {
+                    let ty = &field.ty;
+                    if uses_type_params(ty, &self.input.generics) {
+                        from_bounds.push(syn::parse_quote!(#ty: ::core::default::Default));
+                    }
+
+                    exclusive.push((quote::quote!(#( #cfg )*), field.ident.clone()));
+                }
+
+                continue;
+            }
+
+            if filter && default_value.is_some() {
+                self.errors.push(syn::Error::new_spanned(
+                    &field,
+                    format_args!(
+                        "`#[substruct_default]` cannot be used on a field included in `{name}`"
+                    ),
+                ));
+            }
+
+            if filter {
+                if field.ident.is_none() {
+                    field.ident = names.next().cloned();
+                }
+
+                if from_fn.is_some() && with.is_some() {
+                    self.errors.push(syn::Error::new_spanned(
+                        &field,
+                        "`from_fn` and `with` cannot be used on the same field",
+                    ));
+                }
+
+                if split.is_some() && (from_fn.is_some() || into) {
+                    self.errors.push(syn::Error::new_spanned(
+                        &field,
+                        "`split` cannot be combined with `from_fn` or `into`",
+                    ));
+                }
+
+                if into && (from_fn.is_some() || with.is_some()) {
+                    self.errors.push(syn::Error::new_spanned(
+                        &field,
+                        "`into` cannot be combined with `from_fn` or `with`",
+                    ));
+                }
+
+                if map_into && (from_fn.is_some() || with.is_some() || into || split.is_some()) {
+                    self.errors.push(syn::Error::new_spanned(
+                        &field,
+                        "`map_into` cannot be combined with other transforms",
+                    ));
+                }
+
+                let ty = &field.ty;
+                let try_into = via.map(|_| match &field.ident {
+                    Some(ident) => {
+                        quote::format_ident!("{}", ident.unraw().to_string().to_upper_camel_case())
+                    }
+                    None => quote::format_ident!("Field{}", index),
+                });
+                if let Some(variant) = &try_into {
+                    if from_fn.is_some() || with.is_some() || into || map_into || split.is_some() {
+                        self.errors.push(syn::Error::new_spanned(
+                            &field,
+                            "`via` cannot be combined with other transforms",
+                        ));
+                    }
+
+                    if uses_type_params(ty, &self.input.generics)
+                        || uses_type_params(&parent_ty, &self.input.generics)
+                    {
+                        self.errors.push(syn::Error::new_spanned(
+                            &field,
+                            "`via` cannot be used on fields whose type uses generic parameters",
+                        ));
+                    }
+
+                    let field_name = match &field.ident {
+                        Some(ident) => ident.unraw().to_string(),
+                        None => index.to_string(),
+                    };
+                    try_into_errors.push((
+                        variant.clone(),
+                        format!("Converting the `{field_name}` field failed."),
+                        quote::quote!(<#parent_ty as ::core::convert::TryInto<#ty>>::Error),
+                        format!("failed to convert the `{field_name}` field"),
+                    ));
+                }
+
+                if uses_type_params(&parent_ty, &self.input.generics) {
+                    eq_bounds.push(syn::parse_quote!(#parent_ty: ::core::cmp::PartialEq));
+                }
+                if into && uses_type_params(ty, &self.input.generics) {
+                    into_bounds.push(syn::parse_quote!(#ty: ::core::convert::Into<#parent_ty>));
+                }
+                if into && uses_type_params(&parent_ty, &self.input.generics) {
+                    from_bounds.push(syn::parse_quote!(#parent_ty: ::core::convert::Into<#ty>));
+                }
+                if let (true, Some(parent_elem), Some(elem)) =
+                    (map_into, element_type(&parent_ty), element_type(ty))
+                {
+                    if uses_type_params(elem, &self.input.generics) {
+                        into_bounds
+                            .push(syn::parse_quote!(#elem: ::core::convert::Into<#parent_elem>));
+                    }
+                    if uses_type_params(parent_elem, &self.input.generics) {
+                        from_bounds
+                            .push(syn::parse_quote!(#parent_elem: ::core::convert::Into<#elem>));
+                    }
+                }
+
+                included.insert(
+                    id,
+                    IncludedField {
+                        from_fn,
+                        with,
+                        into,
+                        map_into,
+                        ident: field.ident.clone(),
+                        split: split.map(|split: Vec<SplitField>| {
+                            split.into_iter().map(|split| split.name).collect()
+                        }),
+                        try_into,
+                        cfg,
+                    },
+                );
+            } else if let Some(value) = default_value {
+                defaulted.insert(id, value.into_token_stream());
+            } else if default {
+                if uses_type_params(&field.ty, &self.input.generics) {
+                    let ty = &field.ty;
+                    into_bounds.push(syn::parse_quote!(#ty: ::core::default::Default));
+                }
+
+                defaulted.insert(id, quote::quote!(::core::default::Default::default()));
+            } else {
+                excluded.insert(id, field.ty);
+            }
+        }
+
+        for (ident, _) in &tla.narrow {
+            if !included.contains_key(&IdentOrIndex::Ident(ident.clone())) {
+                self.errors.push(syn::Error::new(
+                    ident.span(),
+                    format_args!("`{ident}` is not a field of `{original}` included in `{name}`"),
+                ));
+            }
+        }
+
+        if tla.trim_generics
+            && (tla.borrow || tla.optional || tla.merges.is_some() || !tla.chain_to.is_empty())
+        {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "`trim_generics` cannot be combined with `borrow`, `optional`, `merges`, or \
+                 `chain_to`",
+            ));
+            return;
+        }
+
+        if !tla.extras.is_empty()
+            && (tla.borrow
+                || tla.optional
+                || tla.trim_generics
+                || tla.roundtrip
+                || !tla.chain_to.is_empty())
+        {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "`extra` cannot be combined with `borrow`, `optional`, `trim_generics`, \
+                 `roundtrip`, or `chain_to`",
+            ));
+            return;
+        }
+
+        if tla.roundtrip && (tla.borrow || tla.optional || tla.trim_generics) {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "`roundtrip` cannot be combined with `borrow`, `optional`, or `trim_generics`",
+            ));
+            return;
+        }
+
+        if tla.partial_eq_parent
+            && (tla.borrow || tla.optional || included.values().any(IncludedField::is_transformed))
+        {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "`partial_eq_parent` cannot be combined with `borrow`, `optional`, or field \
+                 transforms",
+            ));
+            return;
+        }
+
+        if !exclusive.is_empty() && (tla.borrow || tla.optional) {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "`borrow` and `optional` cannot be used on structs with `exclusive` fields",
+            ));
+            return;
+        }
+
+        if tla.assert_subset {
+            self.emit_subset_assertion(substruct, &included);
+        }
+
+        if tla.borrow {
+            self.emit_borrow_conversions(substruct, &included);
+            return;
+        }
+
+        if tla.optional {
+            self.emit_optional_conversions(substruct, &included);
+            return;
+        }
+
+        // With `rest_default`, the excluded fields are filled in using the
+        // parent's `Default` impl instead of being passed as arguments.
+        if tla.rest_default {
+            if matches!(fields, syn::Fields::Unnamed(_)) {
+                self.errors.push(syn::Error::new_spanned(
+                    &substruct.ident,
+                    "`rest_default` cannot be used with tuple structs",
+                ));
+                return;
+            }
+
+            if !self.input.generics.params.is_empty() {
+                into_bounds
+                    .push(syn::parse_quote!(#original #parent_generics: ::core::default::Default));
+            }
+
+            excluded.clear();
+        }
+        let rest = tla
+            .rest_default
+            .then(|| quote::quote!(..::core::default::Default::default()));
+
+        // Generic parameters of the parent which were removed from the child
+        // by `trim_generics`. Trait impls need to declare them alongside the
+        // child's own parameters.
+        let trimmed = trimmed_generics(&self.input.generics, &substruct.generics);
+        let full_generics = merge_generics(&substruct.generics, &trimmed);
+        let (trait_impl_generics, _, _) = full_generics.split_for_impl();
+
+        let into_generics = with_bounds(&full_generics, into_bounds);
+        let from_generics = with_bounds(&full_generics, from_bounds);
+        let into_where = &into_generics.where_clause;
+        let from_where = &from_generics.where_clause;
+
+        let args: Vec<_> = excluded
+            .keys()
+            .cloned()
+            .map(|key| key.into_ident())
+            .collect();
+        let types: Vec<_> = excluded.values().collect();
+        // The types of the `into_<parent>` arguments, and the expressions used
+        // to convert each argument back into the type of the field.
+        let (arg_types, arg_values): (Vec<_>, Vec<_>) = args
+            .iter()
+            .zip(&types)
+            .map(|(arg, ty)| match () {
+                _ if tla.ref_args => (
+                    quote::quote!(&#ty),
+                    quote::quote!(::core::clone::Clone::clone(#arg)),
+                ),
+                _ if tla.into_args => (
+                    quote::quote!(impl ::core::convert::Into<#ty>),
+                    quote::quote!(::core::convert::Into::into(#arg)),
+                ),
+                _ => (quote::quote!(#ty), quote::quote!(#arg)),
+            })
+            .unzip();
+
+        let inc_dst: Vec<_> = included.keys().collect();
+        // Renumber source indexes so they refer to the smaller struct
+        let inc_src: Vec<_> = included
+            .values()
+            .enumerate()
+            .map(|(index, field)| match &field.ident {
+                Some(ident) => IdentOrIndex::Ident(ident.clone()),
+                None => IdentOrIndex::Index(index),
+            })
+            .collect();
+        // Fields shared through the `core` are accessed through its field
+        // within the struct.
+        let core = tla.core.as_ref().map(|core| {
+            let shared: Vec<_> = self
+                .core_fields(core)
+                .into_iter()
+                .filter_map(|field| field.ident)
+                .collect();
+
+            (core, core_member(core), shared)
+        });
+        let in_core = |id: &IdentOrIndex| match (id, &core) {
+            (IdentOrIndex::Ident(ident), Some((_, _, shared))) => shared.contains(ident),
+            _ => false,
+        };
+        let inc_read: Vec<_> = included
+            .keys()
+            .zip(&inc_src)
+            .map(|(id, src)| match &core {
+                Some((_, member, _)) if in_core(id) => quote::quote!(#member.#src),
+                _ => quote::quote!(#src),
+            })
+            .collect();
+        let exc: Vec<_> = excluded.keys().collect();
+        let inc_cfg: Vec<_> = included.keys().map(|id| &cfgs[id]).collect();
+        let exc_cfg: Vec<_> = excluded.keys().map(|id| &cfgs[id]).collect();
+        let def: Vec<_> = defaulted.keys().collect();
+        let def_value: Vec<_> = defaulted.values().collect();
+        let def_cfg: Vec<_> = defaulted.keys().map(|id| &cfgs[id]).collect();
+        // Compute each parent field from the fields of the child, where `read`
+        // generates the expression used to read a field of `self`.
+        let inc_value = |read: &dyn Fn(&dyn ToTokens) -> TokenStream| -> Vec<TokenStream> {
+            included
+                .values()
+                .zip(&inc_read)
+                .map(|(field, src)| match (&field.from_fn, &field.with) {
+                    _ if field.split.is_some() => {
+                        let split: Vec<_> = field
+                            .split
+                            .iter()
+                            .flatten()
+                            .map(|split| read(split))
+                            .collect();
+                        match &field.with {
+                            Some(with) => quote::quote!(#with::into_parent(#( #split ),*)),
+                            None => quote::quote!(( #( #split, )* )),
+                        }
+                    }
+                    (Some(from_fn), _) => {
+                        let src = read(src);
+                        quote::quote!(#from_fn(#src, #( &#args ),*))
+                    }
+                    (None, Some(with)) => {
+                        let src = read(src);
+                        quote::quote!(#with::into_parent(#src))
+                    }
+                    (None, None) if field.into || field.try_into.is_some() => {
+                        let src = read(src);
+                        quote::quote!(::core::convert::Into::into(#src))
+                    }
+                    (None, None) if field.map_into => map_into(read(src)),
+                    (None, None) => read(src),
+                })
+                .collect()
+        };
+        let error = quote::format_ident!("{}ConversionError", name);
+        let inc_extract: Vec<_> = included
+            .iter()
+            .map(|(dst, field)| match &field.with {
+                Some(with) => quote::quote!(#with::from_parent(value.#dst)),
+                None if field.try_into.is_some() => {
+                    let variant = &field.try_into;
+                    quote::quote!(
+                        ::core::convert::TryInto::try_into(value.#dst).map_err(#error::#variant)?
+                    )
+                }
+                None if field.into => quote::quote!(::core::convert::Into::into(value.#dst)),
+                None if field.map_into => map_into(quote::quote!(value.#dst)),
+                None => quote::quote!(value.#dst),
+            })
+            .collect();
+        // Split fields are destructured into locals before constructing the
+        // struct.
+        let mut inc_split = Vec::new();
+        let mut inc_init = Vec::new();
+        let mut core_init = Vec::new();
+        for (((id, field), src), extract) in included.iter().zip(&inc_src).zip(&inc_extract) {
+            let cfg = &field.cfg;
+            let cfg = quote::quote!(#( #cfg )*);
+            match &field.split {
+                None if in_core(id) => core_init.push(quote::quote!(#cfg #src: #extract)),
+                Some(split) => {
+                    let locals: Vec<_> = split
+                        .iter()
+                        .map(|name| quote::format_ident!("split_{}", name))
+                        .collect();
+
+                    inc_split.push(quote::quote!(#cfg let ( #( #locals, )* ) = #extract;));
+                    inc_init.push(quote::quote!(#( #cfg #split: #locals ),*));
+                }
+                None => inc_init.push(quote::quote!(#cfg #src: #extract)),
+            }
+        }
+        if let Some((core, member, _)) = &core {
+            inc_init.push(quote::quote!(#member: #core { #( #core_init, )* }));
+        }
+
+        // The `PhantomData` fields added by `phantom`, which come after all the
+        // other fields.
+        let phantom: Vec<_> = match &substruct.data {
+            syn::Data::Struct(data) if tla.phantom => data
+                .fields
+                .iter()
+                .enumerate()
+                .filter_map(|(index, field)| match &field.ident {
+                    Some(ident) if ident.to_string().starts_with("__substruct_phantom_") => {
+                        Some(IdentOrIndex::Ident(ident.clone()))
+                    }
+                    Some(_) => None,
+                    None if index >= included.len() => Some(IdentOrIndex::Index(index)),
+                    None => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let construct = |read: &dyn Fn(&dyn ToTokens) -> TokenStream| {
+            let inc_value = inc_value(read);
+
+            quote::quote! {
+                #original {
+                    #( #inc_cfg #inc_dst: #inc_value, )*
+                    #( #exc_cfg #exc: #args, )*
+                    #( #def_cfg #def: #def_value, )*
+                    #rest
+                }
+            }
+        };
+
+        let validate_parent: Option<syn::Path> = match (&tla.validate_parent, tla.checked) {
+            (Some(_), true) => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "`checked` cannot be used together with `validate_parent`",
+                ));
+                return;
+            }
+            (None, true) if tla.validate_error.is_none() => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "`checked` requires `validate_error` to be specified",
+                ));
+                return;
+            }
+            (Some(check), false) => Some(check.clone()),
+            (None, true) => Some(syn::parse_quote!(#original::validate)),
+            (None, false) => None,
+        };
+        let validate = match (&validate_parent, &tla.validate_error) {
+            (Some(check), Some(error)) => Some((check, error)),
+            (None, None) => None,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "`validate_parent` and `validate_error` must be specified together",
+                ));
+                return;
+            }
+        };
+
+        let output = match validate {
+            Some((_, error)) => {
+                quote::quote!(::core::result::Result<#original #parent_generics, #error>)
+            }
+            None => quote::quote!(#original #parent_generics),
+        };
+        let body = |construct: TokenStream| match validate {
+            Some((check, _)) => quote::quote! {
+                let parent = #construct;
+                #check(&parent)?;
+                ::core::result::Result::Ok(parent)
+            },
+            None => construct,
+        };
+
+        // Parameters removed by `trim_generics` are declared on the methods
+        // instead, along with any bounds that might refer to them.
+        let (method_generics, _, _) = trimmed.split_for_impl();
+        let (into_impl_where, into_method_where) = match trimmed.params.is_empty() {
+            true => (into_where.as_ref(), None),
+            false => (where_clause, into_where.as_ref()),
+        };
+
+        // Only plain moves can be performed in a `const fn`.
+        if tla.const_fn
+            && (validate.is_some()
+                || tla.ref_args
+                || tla.into_args
+                || tla.rest_default
+                || !defaulted.is_empty()
+                || included.values().any(IncludedField::is_transformed))
+        {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "`const` cannot be used together with field transforms, defaulted fields, \
+                 validation, `ref_args`, or `into_args`",
+            ));
+            return;
+        }
+        let constness = tla.const_fn.then(|| quote::quote!(const));
+
+        let arg_convert = match tla.ref_args || tla.into_args {
+            true => quote::quote!(#( #exc_cfg let #args: #types = #arg_values; )*),
+            false => TokenStream::new(),
+        };
+
+        if tla.ref_args && tla.into_args {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "`ref_args` cannot be used together with `into_args`",
+            ));
+            return;
+        }
+
+        let into_body = body(construct(&|src| quote::quote!(self.#src)));
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            impl #impl_generics #name #ty_generics
+            #into_impl_where
+            {
+                #[doc = #doc]
+                #( #[doc(alias = #aliases)] )*
+                #( #[#method_attrs] )*
+                pub #constness fn #method #method_generics(self, #( #exc_cfg #args: #arg_types, )*) -> #output
+                #into_method_where
+                {
+                    #arg_convert
+                    #into_body
+                }
+            }
+        });
+
+        if tla.partial_eq_parent {
+            let eq_generics = with_bounds(&full_generics, eq_bounds);
+            let eq_where = &eq_generics.where_clause;
+
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #trait_impl_generics ::core::cmp::PartialEq<#original #parent_generics> for #name #ty_generics
+                #eq_where
+                {
+                    fn eq(&self, other: &#original #parent_generics) -> bool {
+                        #(
+                            #inc_cfg
+                            if self.#inc_read != other.#inc_dst {
+                                return false;
+                            }
+                        )*
+
+                        true
+                    }
+                }
+            });
+        }
+
+        if let Some(sibling) = &tla.merges {
+            if let Some((sibling_ty, merged)) =
+                self.merged_fields(substruct, sibling, &included, &excluded)
+            {
+                let merged_cfg: Vec<_> = merged.keys().map(|id| &cfgs[id]).collect();
+                let merged_args: Vec<_> = merged
+                    .keys()
+                    .cloned()
+                    .map(IdentOrIndex::into_ident)
+                    .collect();
+                let merged_src: Vec<_> = merged.values().collect();
+
+                // The excluded fields which are not provided by the sibling are
+                // taken as arguments, the same way as by `into_<parent>`.
+                let rest: Vec<_> = excluded
+                    .keys()
+                    .zip(args.iter().zip(&types))
+                    .zip(arg_types.iter().zip(&arg_values))
+                    .filter(|((id, _), _)| !merged.contains_key(*id))
+                    .map(|((id, (arg, ty)), (arg_ty, value))| (&cfgs[id], arg, ty, arg_ty, value))
+                    .collect();
+                let rest_cfg: Vec<_> = rest.iter().map(|rest| rest.0).collect();
+                let rest_args: Vec<_> = rest.iter().map(|rest| rest.1).collect();
+                let rest_types: Vec<_> = rest.iter().map(|rest| rest.2).collect();
+                let rest_arg_types: Vec<_> = rest.iter().map(|rest| rest.3).collect();
+                let rest_values: Vec<_> = rest.iter().map(|rest| rest.4).collect();
+                let rest_convert = match tla.ref_args || tla.into_args {
+                    true => quote::quote!(
+                        #( #rest_cfg let #rest_args: #rest_types = #rest_values; )*
+                    ),
+                    false => TokenStream::new(),
+                };
+                let doc =
+                    format!("Combine `self` with a [`{sibling}`] to construct a [`{original}`].");
+
+                self.tokens.extend(quote::quote! {
+                    #item_attrs
+                    impl #impl_generics #name #ty_generics
+                    #into_where
+                    {
+                        #[doc = #doc]
+                        pub fn merge(
+                            self,
+                            other: #sibling_ty,
+                            #( #rest_cfg #rest_args: #rest_arg_types, )*
+                        ) -> #output {
+                            #( #merged_cfg let #merged_args = other.#merged_src; )*
+                            #rest_convert
+                            #into_body
+                        }
+                    }
+                });
+            }
+        }
+
+        if tla.by_ref {
+            let method = syn::Ident::new(
+                &format!("to_{}", tla.method_case.apply(original)),
+                Span::call_site(),
+            );
+            let doc =
+                format!("Convert `&self` into a [`{original}`] by cloning each of its fields.");
+            let to_body = body(construct(
+                &|src| quote::quote!(::core::clone::Clone::clone(&self.#src)),
+            ));
+
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #impl_generics #name #ty_generics
+                #into_impl_where
+                {
+                    #[doc = #doc]
+                    #( #[#method_attrs] )*
+                    pub fn #method #method_generics(&self, #( #exc_cfg #args: #arg_types, )*) -> #output
+                    #into_method_where
+                    {
+                        #arg_convert
+                        #to_body
+                    }
+                }
+            });
+        }
+
+        let extract_check = tla.debug_assert_on_extract.as_ref().map(|check| {
+            let message = format!("the extract check failed when converting into `{name}`");
+            quote::quote!(::core::debug_assert!(#check(&value), #message);)
+        });
+
+        let extra_idents: Vec<_> = tla.extras.iter().map(|(ident, _)| ident).collect();
+        let extra_types = tla.extras.iter().map(|(_, ty)| ty);
+        let exclusive_cfg = exclusive.iter().map(|(cfg, _)| cfg);
+        let exclusive_idents = exclusive.iter().map(|(_, ident)| ident);
+        let extract = quote::quote! {
+            #name {
+                #( #inc_init, )*
+                #( #exclusive_cfg #exclusive_idents: ::core::default::Default::default(), )*
+                #( #extra_idents, )*
+                #( #phantom: ::core::marker::PhantomData, )*
+            }
+        };
+
+        if !try_into_errors.is_empty() {
+            let vis = &substruct.vis;
+            let variants: Vec<_> = try_into_errors
+                .iter()
+                .map(|(variant, ..)| variant)
+                .collect();
+            let variant_docs = try_into_errors.iter().map(|(_, doc, ..)| doc);
+            let errors = try_into_errors.iter().map(|(_, _, error, _)| error);
+            let messages = try_into_errors.iter().map(|(_, _, _, message)| message);
+            let doc =
+                format!("An error produced when converting a [`{original}`] into a [`{name}`].");
+
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                #[doc = #doc]
+                #[derive(Debug)]
+                #vis enum #error {
+                    #(
+                        #[doc = #variant_docs]
+                        #variants(#errors),
+                    )*
+                }
+
+                #item_attrs
+                impl ::core::fmt::Display for #error {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        match self {
+                            #( Self::#variants(_) => f.write_str(#messages), )*
+                        }
+                    }
+                }
+
+                #item_attrs
+                impl ::core::error::Error for #error {}
+            });
+        }
+
+        let (extract_output, extract) = match try_into_errors.is_empty() {
+            true => (quote::quote!(#name #ty_generics), extract),
+            false => (
+                quote::quote!(::core::result::Result<#name #ty_generics, #error>),
+                quote::quote!(::core::result::Result::Ok(#extract)),
+            ),
+        };
+
+        // The extra fields have to be provided when converting from the parent
+        // so there is a method on the parent instead of a `From` impl.
+        if !tla.extras.is_empty() {
+            let method = syn::Ident::new(
+                &format!("into_{}", tla.method_case.apply(name)),
+                Span::call_site(),
+            );
+            let doc = format!("Convert `self` into a [`{name}`] with the given extra fields.");
+
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #trait_impl_generics #original #parent_generics
+                #from_where
+                {
+                    #[doc = #doc]
+                    pub fn #method(self, #( #extra_idents: #extra_types, )*) -> #extract_output {
+                        let value = self;
+                        #extract_check
+                        #( #inc_split )*
+
+                        #extract
+                    }
+                }
+            });
+        } else if try_into_errors.is_empty() {
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #trait_impl_generics ::core::convert::From<#original #parent_generics> for #name #ty_generics
+                #from_where
+                {
+                    fn from(value: #original #parent_generics) -> Self {
+                        #extract_check
+                        #( #inc_split )*
+
+                        #extract
+                    }
+                }
+            });
+        } else {
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #trait_impl_generics ::core::convert::TryFrom<#original #parent_generics> for #name #ty_generics
+                #from_where
+                {
+                    type Error = #error;
+
+                    fn try_from(value: #original #parent_generics) -> #extract_output {
+                        #extract_check
+                        #( #inc_split )*
+
+                        #extract
+                    }
+                }
+            });
+        }
+
+        // Arguments to a method call cannot be gated behind `#[cfg]`, so there
+        // is no way to pass the default values for them.
+        if tla.fill_default && exc_cfg.iter().any(|cfg| !cfg.is_empty()) {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "`fill_default` cannot be used when an excluded field has a `#[cfg]` attribute",
+            ));
+            return;
+        }
+
+        // Conversions are never emitted for the parent itself, so this can't
+        // produce an impl that overlaps with the blanket `impl From<T> for T`.
+        // Each generated struct is a distinct type, so even when several of
+        // them include every field their impls don't conflict either.
+        if (excluded.is_empty() || tla.fill_default) && !tla.no_reverse_from {
+            // With `fill_default`, excluded fields are filled in with their
+            // default values.
+            let fill_bounds = types
+                .iter()
+                .filter(|ty| uses_type_params(ty, &self.input.generics))
+                .map(|ty| syn::parse_quote!(#ty: ::core::default::Default))
+                .collect();
+            let reverse_generics = with_bounds(&into_generics, fill_bounds);
+            let reverse_where = &reverse_generics.where_clause;
+            let fill = types.iter().map(|ty| match tla.ref_args {
+                true => quote::quote!(&<#ty as ::core::default::Default>::default()),
+                false => quote::quote!(<#ty as ::core::default::Default>::default()),
+            });
+
+            self.tokens.extend(match validate {
+                Some((_, error)) => quote::quote! {
+                    #item_attrs
+                    impl #trait_impl_generics ::core::convert::TryFrom<#name #ty_generics> for #original #parent_generics
+                    #reverse_where
+                    {
+                        type Error = #error;
+
+                        fn try_from(value: #name #ty_generics) -> ::core::result::Result<Self, #error> {
+                            value.#method(#( #fill ),*)
+                        }
+                    }
+                },
+                None => quote::quote! {
+                    #item_attrs
+                    impl #trait_impl_generics ::core::convert::From<#name #ty_generics> for #original #parent_generics
+                    #reverse_where
+                    {
+                        fn from(value: #name #ty_generics) -> Self {
+                            value.#method(#( #fill ),*)
+                        }
+                    }
+                },
+            })
+        }
+
+        if tla.from_ref {
+            if !excluded.is_empty() {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "`from_ref` requires all of the parent's fields to be included in the \
+                     generated struct",
+                ));
+                return;
+            }
+
+            let clone_bounds = match &substruct.data {
+                syn::Data::Struct(data) => data
+                    .fields
+                    .iter()
+                    .map(|field| &field.ty)
+                    .filter(|ty| uses_type_params(ty, &self.input.generics))
+                    .map(|ty| syn::parse_quote!(#ty: ::core::clone::Clone))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let mut ref_generics = with_bounds(&into_generics, clone_bounds);
+            ref_generics
+                .params
+                .insert(0, syn::parse_quote!('__substruct_ref));
+            let (ref_impl_generics, _, ref_where) = ref_generics.split_for_impl();
+            let ref_body = body(construct(
+                &|src| quote::quote!(::core::clone::Clone::clone(&value.#src)),
+            ));
+
+            self.tokens.extend(match validate {
+                Some((_, error)) => quote::quote! {
+                    #item_attrs
+                    impl #ref_impl_generics ::core::convert::TryFrom<&'__substruct_ref #name #ty_generics> for #original #parent_generics
+                    #ref_where
+                    {
+                        type Error = #error;
+
+                        fn try_from(value: &'__substruct_ref #name #ty_generics) -> ::core::result::Result<Self, #error> {
+                            #ref_body
+                        }
+                    }
+                },
+                None => quote::quote! {
+                    #item_attrs
+                    impl #ref_impl_generics ::core::convert::From<&'__substruct_ref #name #ty_generics> for #original #parent_generics
+                    #ref_where
+                    {
+                        fn from(value: &'__substruct_ref #name #ty_generics) -> Self {
+                            #ref_body
+                        }
+                    }
+                },
+            })
+        }
+
+        if tla.roundtrip {
+            if exc_cfg.iter().any(|cfg| !cfg.is_empty()) {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "`roundtrip` cannot be used when an excluded field has a `#[cfg]` attribute",
+                ));
+                return;
+            }
+
+            let into_call = quote::quote!(::core::clone::Clone::clone(self).#method(#( #args ),*));
+            let mut bounds: Vec<syn::WherePredicate> = vec![
+                syn::parse_quote!(Self: ::core::clone::Clone),
+                syn::parse_quote!(Self: ::core::cmp::PartialEq),
+            ];
+            if let Some(from_where) = from_where {
+                bounds.extend(from_where.predicates.iter().cloned());
+            }
+            let roundtrip_generics = with_bounds(&into_generics, bounds);
+            let roundtrip_where = &roundtrip_generics.where_clause;
+
+            let parent = match validate {
+                Some(_) => quote::quote! {
+                    let ::core::result::Result::Ok(parent) = #into_call else {
+                        return false;
+                    };
+                },
+                None => quote::quote!(let parent = #into_call;),
+            };
+            let check = match try_into_errors.is_empty() {
+                true => {
+                    quote::quote!(<Self as ::core::convert::From<#original #parent_generics>>::from(parent) == *self)
+                }
+                false => quote::quote! {
+                    match <Self as ::core::convert::TryFrom<#original #parent_generics>>::try_from(parent) {
+                        ::core::result::Result::Ok(child) => child == *self,
+                        ::core::result::Result::Err(_) => false,
+                    }
+                },
+            };
+            let doc = format!(
+                "Check that converting `self` into a [`{original}`] and back again produces \
+                 the same value."
+            );
+
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #impl_generics #name #ty_generics
+                #roundtrip_where
+                {
+                    #[doc = #doc]
+                    pub fn roundtrip(&self, #( #exc_cfg #args: #arg_types, )*) -> bool {
+                        #parent
+                        #check
+                    }
+                }
+            });
+        }
+
+        if !tla.chain_to.is_empty() && included.values().any(|field| field.try_into.is_some()) {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "`chain_to` cannot be used when fields are converted with `via`",
+            ));
+            return;
+        }
+
+        for leaf in &tla.chain_to {
+            let method = syn::Ident::new(
+                &format!("into_{}", leaf.unraw().to_string().to_snake_case()),
+                Span::call_site(),
+            );
+            let doc = format!("Convert `self` into a [`{leaf}`] by way of [`{name}`].");
+
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #impl_generics #original #parent_generics
+                #from_where
+                {
+                    #[doc = #doc]
+                    pub fn #method(self) -> #leaf #ty_generics {
+                        ::core::convert::From::from(self)
+                    }
+                }
+
+                #item_attrs
+                impl #impl_generics ::core::convert::From<#original #parent_generics> for #leaf #ty_generics
+                #from_where
+                {
+                    fn from(value: #original #parent_generics) -> Self {
+                        let mid: #name #ty_generics = ::core::convert::From::from(value);
+                        ::core::convert::From::from(mid)
+                    }
+                }
+            });
+        }
+
+        if tla.debug_entries {
+            let method =
+                quote::format_ident!("{}_entries", name.unraw().to_string().to_snake_case());
+            let doc =
+                format!("Iterate over the name and value of each field included in [`{name}`].");
+            let (parent_impl, _, parent_where) = self.input.generics.split_for_impl();
+            let mut where_clause = parent_where
+                .cloned()
+                .unwrap_or_else(|| syn::parse_quote!(where));
+            let mut keys = Vec::new();
+            let mut values = Vec::new();
+            let mut entry_cfgs = Vec::new();
+
+            for (index, field) in fields.iter().enumerate() {
+                let id = match field.ident.clone() {
+                    Some(ident) => IdentOrIndex::Ident(ident),
+                    None => IdentOrIndex::Index(index),
+                };
+
+                if !included.contains_key(&id) {
+                    continue;
+                }
+
+                let ty = &field.ty;
+                if uses_type_params(ty, &self.input.generics) {
+                    where_clause
+                        .predicates
+                        .push(syn::parse_quote!(#ty: ::core::fmt::Debug));
+                }
+
+                keys.push(match &id {
+                    IdentOrIndex::Ident(ident) => ident.unraw().to_string(),
+                    IdentOrIndex::Index(index) => index.to_string(),
+                });
+                values.push(id);
+                entry_cfgs.push(cfg_attrs(&field.attrs));
+            }
+
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #parent_impl #original #parent_generics
+                #where_clause
+                {
+                    #[doc = #doc]
+                    pub fn #method(
+                        &self,
+                    ) -> impl ::core::iter::Iterator<Item = (&'static str, &dyn ::core::fmt::Debug)> {
+                        // Each entry is chained on separately so that fields
+                        // with `#[cfg]`s can be left out.
+                        let entries = ::core::iter::empty::<(&'static str, &dyn ::core::fmt::Debug)>();
+                        #(
+                            #( #entry_cfgs )*
+                            let entries = ::core::iter::Iterator::chain(
+                                entries,
+                                ::core::iter::once((#keys, &self.#values as &dyn ::core::fmt::Debug)),
+                            );
+                        )*
+
+                        entries
+                    }
+                }
+            });
+        }
+    }
+
+    /// Emit the compile-time check for the `max_size` option.
+    ///
+    /// Lifetime parameters are filled in with `'static` since they don't affect
+    /// the size of the struct.
+    fn emit_size_assertion(&mut self, input: &syn::DeriveInput, max_size: &syn::Expr) {
+        let item_attrs = emitted_attrs(input);
+        let name = &input.ident;
+        let mut args = Vec::new();
+        for param in &input.generics.params {
+            match param {
+                syn::GenericParam::Lifetime(_) => args.push(quote::quote!('static)),
+                _ => {
+                    self.errors.push(syn::Error::new_spanned(
+                        max_size,
+                        "`max_size` cannot be used on structs with type or const parameters",
+                    ));
+                    return;
+                }
+            }
+        }
+
+        let ty = match args.is_empty() {
+            true => quote::quote!(#name),
+            false => quote::quote!(#name<#( #args ),*>),
+        };
+        let message = format!(
+            "`{name}` is larger than the size given by `max_size = {}`",
+            max_size.to_token_stream()
+        )
+        .replace('{', "{{")
+        .replace('}', "}}");
+
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            const _: () = ::core::assert!(
+                ::core::mem::size_of::<#ty>() <= #max_size,
+                #message
+            );
+        });
+    }
+
+    /// Emit the compile-time check for the `assert_subset` option.
+    ///
+    /// This is a function which is never called. It takes both the parent and
+    /// the generated struct and requires each untransformed field to have the
+    /// same type in both. Transformed fields only need to still exist in the
+    /// parent since their types are expected to differ.
+    fn emit_subset_assertion(
+        &mut self,
+        substruct: &syn::DeriveInput,
+        included: &IndexMap<IdentOrIndex, IncludedField>,
+    ) {
+        let item_attrs = emitted_attrs(substruct);
+        let original = &self.input.ident;
+        let name = &substruct.ident;
+        let tla = &self.args[name];
+        let (_, parent_generics, _) = self.input.generics.split_for_impl();
+        let (_, ty_generics, _) = substruct.generics.split_for_impl();
+
+        let trimmed = trimmed_generics(&self.input.generics, &substruct.generics);
+        let full_generics = merge_generics(&substruct.generics, &trimmed);
+        let (impl_generics, _, where_clause) = full_generics.split_for_impl();
+
+        let checks = included.iter().enumerate().map(|(position, (id, field))| {
+            let cfg = &field.cfg;
+            let child = match &field.ident {
+                Some(ident) => syn::Member::Named(ident.clone()),
+                None => syn::Member::Unnamed(syn::Index::from(position)),
+            };
+
+            if field.is_transformed() {
+                quote::quote!( #( #cfg )* let _ = &parent.#id; )
+            } else if tla.borrow {
+                quote::quote!( #( #cfg )* same_type(&parent.#id, child.#child); )
+            } else if tla.optional {
+                quote::quote! {
+                    #( #cfg )*
+                    same_type(
+                        &::core::option::Option::Some(&parent.#id),
+                        &child.#child.as_ref(),
+                    );
+                }
+            } else {
+                quote::quote!( #( #cfg )* same_type(&parent.#id, &child.#child); )
+            }
+        });
+
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            const _: () = {
+                #[allow(dead_code)]
+                fn assert_subset #impl_generics (
+                    parent: &#original #parent_generics,
+                    child: &#name #ty_generics,
+                )
+                #where_clause
+                {
+                    fn same_type<T: ?::core::marker::Sized>(_: &T, _: &T) {}
+
+                    #( #checks )*
+                }
+            };
+        });
+    }
+
+    /// Emit the conversion from a reference to the parent into a struct with
+    /// the `borrow` option.
+    fn emit_borrow_conversions(
+        &mut self,
+        substruct: &syn::DeriveInput,
+        included: &IndexMap<IdentOrIndex, IncludedField>,
+    ) {
+        let item_attrs = emitted_attrs(substruct);
+        let original = &self.input.ident;
+        let name = &substruct.ident;
+        let (impl_generics, ty_generics, where_clause) = substruct.generics.split_for_impl();
+        let (_, parent_generics, _) = self.input.generics.split_for_impl();
+
+        if included.values().any(IncludedField::is_transformed) {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "field transforms cannot be used with borrow",
+            ));
+            return;
+        }
+
+        let inc_dst: Vec<_> = included.keys().collect();
+        let inc_src: Vec<_> = included
+            .values()
+            .enumerate()
+            .map(|(index, field)| match &field.ident {
+                Some(ident) => IdentOrIndex::Ident(ident.clone()),
+                None => IdentOrIndex::Index(index),
+            })
+            .collect();
+        let inc_cfg = included.values().map(|field| &field.cfg);
+
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            impl #impl_generics ::core::convert::From<&'borrow #original #parent_generics> for #name #ty_generics
+            #where_clause
+            {
+                fn from(value: &'borrow #original #parent_generics) -> Self {
+                    Self {
+                        #( #( #inc_cfg )* #inc_src: &value.#inc_dst, )*
+                    }
+                }
+            }
+        });
+    }
+
+    /// Emit conversions between the struct and a sibling struct that contains
+    /// all of its fields, for the `parent = Sibling` option.
+    fn emit_parent_conversions(&mut self, substruct: &syn::DeriveInput, target: &syn::Ident) {
+        let item_attrs = emitted_attrs(substruct);
+        if !self.errors.is_empty() {
+            return;
+        }
+
+        let original = &self.input.ident;
+        let name = &substruct.ident;
+        let (impl_generics, ty_generics, where_clause) = substruct.generics.split_for_impl();
+        let args = self.args.clone();
+        let tla = &args[name];
+
+        let target_tla = match args.get(target) {
+            Some(tla) if target != original && target != name => tla,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    target.span(),
+                    format_args!("`{target}` is not another struct generated from `{original}`"),
+                ));
+                return;
+            }
+        };
+
+        let unsupported = |tla: &TopLevelArg| {
+            tla.borrow || tla.optional || tla.trim_generics || tla.phantom || !tla.extras.is_empty()
+        };
+        if unsupported(tla) || unsupported(target_tla) {
+            self.errors.push(syn::Error::new(
+                target.span(),
+                "`parent` cannot be used when either struct uses `borrow`, `optional`, \
+                 `trim_generics`, `phantom`, or `extra`",
+            ));
+            return;
+        }
+
+        let fields = match &self.input.data {
+            syn::Data::Struct(syn::DataStruct {
+                fields: syn::Fields::Named(fields),
+                ..
+            }) => &fields.named,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    target.span(),
+                    "`parent` can only be used on structs with named fields",
+                ));
+                return;
+            }
+        };
+
+        let mut target_generics = self.input.generics.clone();
+        if let Some((option, order)) = &target_tla.generic_order {
+            // Any errors here are reported when emitting the target itself.
+            if reorder_generics(&mut target_generics, option, order).is_err() {
+                return;
+            }
+        }
+        let (_, target_ty_generics, _) = target_generics.split_for_impl();
+
+        // Fields present in both structs, along with their names in each.
+        let mut shared = Vec::new();
+        // Fields of the target which are not present in the struct.
+        let mut missing = Vec::new();
+        for field in fields {
+            let input = self.field_input(&field.attrs);
+            let in_child = input.matching(name);
+            let in_target = input.matching(target);
+            let transformed = in_child
+                .into_iter()
+                .chain(in_target)
+                .any(|arg| !arg.options.is_empty());
+            let cfg = cfg_attrs(&field.attrs);
+            let src = self.field_rename(field, name).or(field.ident.clone());
+            let dst = self.field_rename(field, target).or(field.ident.clone());
+
+            match (in_child.is_some(), in_target.is_some()) {
+                _ if transformed => self.errors.push(syn::Error::new_spanned(
+                    field,
+                    format_args!(
+                        "field options cannot be used on fields converted between `{name}` and \
+                         `{target}`"
+                    ),
+                )),
+                (true, false) => self.errors.push(syn::Error::new_spanned(
+                    field,
+                    format_args!("this field is included in `{name}` but not in `{target}`"),
+                )),
+                (true, true) => shared.push((cfg, src, dst)),
+                (false, true) => missing.push((cfg, dst, &field.ty)),
+                (false, false) => (),
+            }
+        }
+
+        if !self.errors.is_empty() {
+            return;
+        }
+
+        let method = syn::Ident::new(
+            &format!("into_{}", tla.method_case.apply(target)),
+            Span::call_site(),
+        );
+        let doc = format!("Convert `self` into a [`{target}`].");
+        let shared_cfg: Vec<_> = shared.iter().map(|(cfg, ..)| cfg).collect();
+        let shared_src: Vec<_> = shared.iter().map(|(_, src, _)| src).collect();
+        let shared_dst: Vec<_> = shared.iter().map(|(.., dst)| dst).collect();
+        let missing_cfg: Vec<_> = missing.iter().map(|(cfg, ..)| cfg).collect();
+        let missing_dst: Vec<_> = missing.iter().map(|(_, dst, _)| dst).collect();
+        let missing_ty = missing.iter().map(|(.., ty)| ty);
+
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            impl #impl_generics #name #ty_generics
+            #where_clause
+            {
+                #[doc = #doc]
+                pub fn #method(self, #( #( #missing_cfg )* #missing_dst: #missing_ty, )*) -> #target #target_ty_generics {
+                    #target {
+                        #( #( #shared_cfg )* #shared_dst: self.#shared_src, )*
+                        #( #( #missing_cfg )* #missing_dst, )*
+                    }
+                }
+            }
+
+            #item_attrs
+            impl #impl_generics ::core::convert::From<#target #target_ty_generics> for #name #ty_generics
+            #where_clause
+            {
+                fn from(value: #target #target_ty_generics) -> Self {
+                    Self {
+                        #( #( #shared_cfg )* #shared_src: value.#shared_dst, )*
+                    }
+                }
+            }
+        });
+
+        if missing.is_empty() {
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #impl_generics ::core::convert::From<#name #ty_generics> for #target #target_ty_generics
+                #where_clause
+                {
+                    fn from(value: #name #ty_generics) -> Self {
+                        value.#method()
+                    }
+                }
+            });
+        }
+    }
+
+    /// Emit `From` impls converting each sibling struct which includes exactly
+    /// the same fields into this one, for the `sibling_from` option.
+    ///
+    /// Siblings where either side of a shared field has field options are
+    /// skipped since the field may have a different type in each.
+    fn emit_sibling_conversions(&mut self, substruct: &syn::DeriveInput) {
+        let item_attrs = emitted_attrs(substruct);
+        if !self.errors.is_empty() {
+            return;
+        }
+
+        let original = &self.input.ident;
+        let name = &substruct.ident;
+        let (impl_generics, ty_generics, where_clause) = substruct.generics.split_for_impl();
+        let args = self.args.clone();
+        let tla = &args[name];
+
+        let fields = match &self.input.data {
+            syn::Data::Struct(data) if !matches!(data.fields, syn::Fields::Unit) => &data.fields,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    name.span(),
+                    "`sibling_from` can only be used on structs with fields",
+                ));
+                return;
+            }
+        };
+
+        let unsupported = |tla: &TopLevelArg| {
+            tla.borrow || tla.optional || tla.trim_generics || tla.phantom || !tla.extras.is_empty()
+        };
+        if unsupported(tla) {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "`sibling_from` cannot be combined with `borrow`, `optional`, \
+                 `trim_generics`, `phantom`, or `extra`",
+            ));
+            return;
+        }
+
+        for (sibling, sibling_tla) in args.iter() {
+            if sibling == original || sibling == name || unsupported(sibling_tla) {
+                continue;
+            }
+
+            // `parent = Sibling` already emits conversions in both directions
+            // when the fields are the same.
+            if tla.parent.as_ref() == Some(sibling) || sibling_tla.parent.as_ref() == Some(name) {
+                continue;
+            }
+
+            // Fields present in both structs, along with their members in each.
+            let mut shared = Vec::new();
+            let mut identical = true;
+            for field in fields {
+                let input = self.field_input(&field.attrs);
+                let position = shared.len();
+
+                match (input.matching(name), input.matching(sibling)) {
+                    (None, None) => continue,
+                    (Some(a), Some(b)) if a.options.is_empty() && b.options.is_empty() => (),
+                    _ => {
+                        identical = false;
+                        break;
+                    }
+                }
+
+                let dst = self.child_member(field, name, position);
+                let src = self.child_member(field, sibling, position);
+                match (dst, src) {
+                    (Some(dst), Some(src)) => shared.push((cfg_attrs(&field.attrs), dst, src)),
+                    _ => {
+                        identical = false;
+                        break;
+                    }
+                }
+            }
+
+            if !identical {
+                continue;
+            }
+
+            let mut sibling_generics = self.input.generics.clone();
+            if let Some((option, order)) = &sibling_tla.generic_order {
+                // Any errors here are reported when emitting the sibling itself.
+                if reorder_generics(&mut sibling_generics, option, order).is_err() {
+                    continue;
+                }
+            }
+            let (_, sibling_ty_generics, _) = sibling_generics.split_for_impl();
+
+            let cfg = shared.iter().map(|(cfg, ..)| cfg);
+            let dst = shared.iter().map(|(_, dst, _)| dst);
+            let src = shared.iter().map(|(.., src)| src);
+
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #impl_generics ::core::convert::From<#sibling #sibling_ty_generics> for #name #ty_generics
+                #where_clause
+                {
+                    fn from(value: #sibling #sibling_ty_generics) -> Self {
+                        Self {
+                            #( #( #cfg )* #dst: value.#src, )*
+                        }
+                    }
+                }
+            });
+        }
+    }
+
+    /// Get the member used to access `field` within the generated struct
+    /// `name`, where `position` is its index among the included fields.
+    ///
+    /// Returns `None` if the struct doesn't have a name for a tuple field.
+    fn child_member(
+        &mut self,
+        field: &syn::Field,
+        name: &syn::Ident,
+        position: usize,
+    ) -> Option<syn::Member> {
+        match &field.ident {
+            Some(ident) => Some(syn::Member::Named(
+                self.field_rename(field, name)
+                    .unwrap_or_else(|| ident.clone()),
+            )),
+            None => match &self.args[name].named {
+                Some(names) => names.get(position).cloned().map(syn::Member::Named),
+                None => Some(syn::Member::Unnamed(syn::Index::from(position))),
+            },
+        }
+    }
+
+    /// Find the excluded fields of the parent which are provided by the sibling
+    /// struct given to `merges`, mapped to the field of the sibling which
+    /// provides each of them. Also returns the type of the sibling.
+    fn merged_fields(
+        &mut self,
+        substruct: &syn::DeriveInput,
+        sibling: &syn::Ident,
+        included: &IndexMap<IdentOrIndex, IncludedField>,
+        excluded: &IndexMap<IdentOrIndex, syn::Type>,
+    ) -> Option<(TokenStream, IndexMap<IdentOrIndex, IdentOrIndex>)> {
+        let original = &self.input.ident;
+        let name = &substruct.ident;
+        let args = self.args.clone();
+
+        let sibling_tla = match args.get(sibling) {
+            Some(tla) if sibling != original && sibling != name => tla,
+            _ => {
+                self.errors.push(syn::Error::new(
+                    sibling.span(),
+                    format_args!("`{sibling}` is not another struct generated from `{original}`"),
+                ));
+                return None;
+            }
+        };
+
+        if sibling_tla.borrow || sibling_tla.optional {
+            self.errors.push(syn::Error::new(
+                sibling.span(),
+                "cannot merge a struct generated with `borrow` or `optional`",
+            ));
+            return None;
+        }
+
+        let mut sibling_generics = self.input.generics.clone();
+        if let Some((option, order)) = &sibling_tla.generic_order {
+            // Any errors here are reported when emitting the sibling itself.
+            if reorder_generics(&mut sibling_generics, option, order).is_err() {
+                return None;
+            }
+        }
+        let (_, sibling_ty_generics, _) = sibling_generics.split_for_impl();
+
+        let fields = match &self.input.data {
+            syn::Data::Struct(data) => &data.fields,
+            _ => return None,
+        };
+
+        // Maps the excluded fields of the parent to the field of the sibling
+        // which provides them.
+        let mut merged = IndexMap::new();
+        let mut sibling_index = 0;
+        for (index, field) in fields.iter().enumerate() {
+            let input = self.field_input(&field.attrs);
+            if input.matching(sibling).is_none() {
+                continue;
+            }
+
+            let id = match field.ident.clone() {
+                Some(ident) => IdentOrIndex::Ident(ident),
+                None => IdentOrIndex::Index(index),
+            };
+            let src = match self.field_rename(field, sibling).or(field.ident.clone()) {
+                Some(ident) => IdentOrIndex::Ident(ident),
+                None => IdentOrIndex::Index(sibling_index),
+            };
+            sibling_index += 1;
+
+            let transformed = input
+                .args
+                .iter()
+                .filter(|arg| !arg.has_flag("default") && arg.expr.evaluate(sibling))
+                .any(|arg| !arg.options.is_empty());
+            let message = match () {
+                _ if transformed => {
+                    format!("field options cannot be used on fields merged from `{sibling}`")
+                }
+                _ if included.contains_key(&id) => {
+                    format!("this field is included in both `{name}` and `{sibling}`")
+                }
+                _ if !excluded.contains_key(&id) => format!(
+                    "this field is filled in by default in `{name}` and cannot be merged from \
+                     `{sibling}`"
+                ),
+                _ => {
+                    merged.insert(id, src);
+                    continue;
+                }
+            };
+
+            self.errors.push(syn::Error::new_spanned(field, message));
+        }
+
+        Some((quote::quote!(#sibling #sibling_ty_generics), merged))
+    }
+
+    /// Emit the `apply_to` method for a struct generated with `optional`.
+    fn emit_optional_conversions(
+        &mut self,
+        substruct: &syn::DeriveInput,
+        included: &IndexMap<IdentOrIndex, IncludedField>,
+    ) {
+        let item_attrs = emitted_attrs(substruct);
+        let original = &self.input.ident;
+        let name = &substruct.ident;
+        let (impl_generics, ty_generics, where_clause) = substruct.generics.split_for_impl();
+        let (_, parent_generics, _) = self.input.generics.split_for_impl();
+
+        if included.values().any(IncludedField::is_transformed) {
+            self.errors.push(syn::Error::new(
+                name.span(),
+                "field transforms cannot be used with optional",
+            ));
+            return;
+        }
+
+        let inc_dst: Vec<_> = included.keys().collect();
+        let inc_src: Vec<_> = included
+            .values()
+            .enumerate()
+            .map(|(index, field)| match &field.ident {
+                Some(ident) => IdentOrIndex::Ident(ident.clone()),
+                None => IdentOrIndex::Index(index),
+            })
+            .collect();
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            impl #impl_generics #name #ty_generics
+            #where_clause
+            {
+                /// Assign each field of `self` that is `Some` to the corresponding
+                /// field of `target`.
+                pub fn apply_to(self, target: &mut #original #parent_generics) {
+                    #(
+                        if let ::core::option::Option::Some(value) = self.#inc_src {
+                            target.#inc_dst = value;
+                        }
+                    )*
+                }
+            }
+        });
+    }
+
+    fn filter_variants(&mut self, data: &mut syn::DataEnum, name: &syn::Ident) {
+        data.variants = std::mem::take(&mut data.variants)
+            .into_pairs()
+            .filter_map(
+                |mut pair| match self.filter_variant(pair.value_mut(), name) {
+                    true => Some(pair),
+                    false => None,
+                },
+            )
+            .collect();
+    }
+
+    fn filter_variant(&mut self, variant: &mut syn::Variant, name: &syn::Ident) -> bool {
+        let substruct = self.field_input(&variant.attrs);
+
+        if substruct.matching(name).is_none() {
+            return false;
+        }
+
+        self.filter_attrs(&mut variant.attrs, name);
+
+        if let Some(docs) = substruct.docs(name) {
+            variant.attrs.retain(|attr| !is_doc_comment(attr));
+            variant.attrs.extend(interpolate_docs(docs, name));
+        }
+        variant.attrs.extend(substruct.attrs(name).iter().cloned());
+
+        // The fields of a variant are carried through unchanged but they may
+        // still have `#[substruct_attr]` attributes.
+        for field in variant.fields.iter_mut() {
+            self.filter_attrs(&mut field.attrs, name);
+        }
+
+        true
+    }
+
+    /// Emit the conversions between an enum and an enum generated from a
+    /// subset of its variants.
+    fn emit_enum_conversions(&mut self, substruct: &syn::DeriveInput, data: &syn::DataEnum) {
+        let item_attrs = emitted_attrs(substruct);
+        let original = &self.input.ident;
+        let name = &substruct.ident;
+        let (impl_generics, ty_generics, where_clause) = substruct.generics.split_for_impl();
+        let (_, parent_generics, _) = self.input.generics.split_for_impl();
+
+        let tla = &self.args[name];
+        let method = tla.method_name(original);
+        let aliases = &tla.method_aliases;
+        let method_attrs = &tla.method_attrs;
+        let doc = tla.conversion_doc(original);
+        let constness = tla.const_fn.then(|| quote::quote!(const));
+
+        let variants = match &substruct.data {
+            syn::Data::Enum(data) => &data.variants,
+            _ => unreachable!(),
+        };
+        let exhaustive = variants.len() == data.variants.len();
+
+        let patterns: Vec<_> = variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                let bindings: Vec<_> = variant
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(index, field)| match &field.ident {
+                        Some(ident) => ident.clone(),
+                        None => syn::Ident::new(&format!("arg{index}"), Span::call_site()),
+                    })
+                    .collect();
+
+                match &variant.fields {
+                    syn::Fields::Named(_) => quote::quote!(#ident { #( #bindings, )* }),
+                    syn::Fields::Unnamed(_) => quote::quote!(#ident ( #( #bindings, )* )),
+                    syn::Fields::Unit => quote::quote!(#ident),
+                }
+            })
+            .collect();
+
+        self.tokens.extend(quote::quote! {
+            #item_attrs
+            impl #impl_generics #name #ty_generics
+            #where_clause
+            {
+                #[doc = #doc]
+                #( #[doc(alias = #aliases)] )*
+                #( #[#method_attrs] )*
+                pub #constness fn #method(self) -> #original #parent_generics {
+                    match self {
+                        #( Self::#patterns => #original::#patterns, )*
+                    }
+                }
+            }
+        });
+
+        if !tla.no_reverse_from {
+            self.tokens.extend(quote::quote! {
+                #item_attrs
+                impl #impl_generics ::core::convert::From<#name #ty_generics> for #original #parent_generics
+                #where_clause
+                {
+                    fn from(value: #name #ty_generics) -> Self {
+                        value.#method()
+                    }
+                }
+            });
+        }
+
+        self.tokens.extend(match exhaustive {
+            true => quote::quote! {
+                #item_attrs
+                impl #impl_generics ::core::convert::From<#original #parent_generics> for #name #ty_generics
+                #where_clause
+                {
+                    fn from(value: #original #parent_generics) -> Self {
+                        match value {
+                            #( #original::#patterns => Self::#patterns, )*
+                        }
+                    }
+                }
+            },
+            // The conversion fails for the excluded variants, in which case the
+            // original value is returned as the error.
+            false => quote::quote! {
+                #item_attrs
+                impl #impl_generics ::core::convert::TryFrom<#original #parent_generics> for #name #ty_generics
+                #where_clause
+                {
+                    type Error = #original #parent_generics;
+
+                    fn try_from(
+                        value: #original #parent_generics
+                    ) -> ::core::result::Result<Self, Self::Error> {
+                        match value {
+                            #( #original::#patterns => ::core::result::Result::Ok(Self::#patterns), )*
+                            value => ::core::result::Result::Err(value),
+                        }
+                    }
+                }
+            },
+        });
+    }
+
+    fn filter_fields_named(&mut self, fields: &mut syn::FieldsNamed, name: &syn::Ident) {
+        let mut named = Punctuated::new();
+
+        for mut field in std::mem::take(&mut fields.named) {
+            let split = self.split_field(&field, name);
+            if !self.filter_field(&mut field, name) {
+                continue;
+            }
+
+            match split {
+                Some(split) => named.extend(split_fields(&field, split)),
+                None => named.push(field),
+            }
+        }
+
+        // Renames and splits may result in two fields with the same name. The
+        // duplicates are dropped so that rustc doesn't report them again. The
+        // parent's fields are left for rustc to check.
+        if *name != self.input.ident {
+            let mut seen = IndexSet::new();
+            named = named
+                .into_iter()
+                .filter(|field| {
+                    let ident = field.ident.as_ref().expect("named field had no ident");
+                    if seen.insert(ident.clone()) {
+                        return true;
+                    }
+
+                    self.errors.push(syn::Error::new(
+                        ident.span(),
+                        format_args!("field `{ident}` is defined multiple times in `{name}`"),
+                    ));
+                    false
+                })
+                .collect();
+        }
+
+        fields.named = named;
+    }
+
+    fn filter_fields_unnamed(&mut self, fields: &mut syn::FieldsUnnamed, name: &syn::Ident) {
+        fields.unnamed = std::mem::take(&mut fields.unnamed)
+            .into_pairs()
+            .filter_map(|mut pair| {
+                let split = self.split_field(pair.value(), name);
+                if !self.filter_field(pair.value_mut(), name) {
+                    return None;
+                }
+
+                if split.is_some() {
+                    self.errors.push(syn::Error::new_spanned(
+                        pair.value(),
+                        "split can only be used on structs with named fields",
+                    ));
+                }
+
+                Some(pair)
+            })
+            .collect();
+    }
+
+    /// Parse the `#[substruct]` attributes on all the fields or enum variants
+    /// of the parent, reporting any errors within them.
+    ///
+    /// This is done once up front since the attributes are looked up many
+    /// times for each generated struct.
+    fn parse_field_inputs(&mut self) {
+        let attrs: Vec<&[syn::Attribute]> = match &self.input.data {
+            syn::Data::Struct(data) => data.fields.iter().map(|f| f.attrs.as_slice()).collect(),
+            syn::Data::Enum(data) => data.variants.iter().map(|v| v.attrs.as_slice()).collect(),
+            syn::Data::Union(data) => data
+                .fields
+                .named
+                .iter()
+                .map(|field| field.attrs.as_slice())
+                .collect(),
+        };
+
+        let mut errors = Vec::new();
+        for attrs in attrs {
+            let input = self.parse_field_input(attrs, &mut errors);
+            self.field_inputs
+                .entry(field_input_key(attrs))
+                .or_insert_with(|| Rc::new(input));
+        }
+
+        self.errors.extend(errors);
+    }
+
+    /// Get the parsed `#[substruct]` attributes on a field or enum variant.
+    ///
+    /// The returned input always ends with an argument matching the parent
+    /// struct.
+    fn field_input(&self, attrs: &[syn::Attribute]) -> Rc<SubstructInput> {
+        match self.field_inputs.get(&field_input_key(attrs)) {
+            Some(input) => input.clone(),
+            // Errors were already reported when the parent was parsed.
+            None => Rc::new(self.parse_field_input(attrs, &mut Vec::new())),
+        }
+    }
+
+    /// Parse the `#[substruct]` attributes on a field or enum variant.
+    fn parse_field_input(
+        &self,
+        attrs: &[syn::Attribute],
+        errors: &mut Vec<syn::Error>,
+    ) -> SubstructInput {
+        // Multiple `#[substruct]` attributes are merged into one.
+        let mut substruct = SubstructInput::default();
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("substruct"))
+        {
+            match attr.parse_args::<SubstructInput>() {
+                Ok(args) => {
+                    substruct.options.extend(args.options);
+                    substruct.args.extend(args.args);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+
+        for option in &substruct.options {
+            let result = match () {
+                _ if option.name == "exclusive" => option.expect_flag(),
+                _ => Err(option.unknown()),
+            };
+
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+
+        for arg in &mut substruct.args {
+            arg.expr.expand_groups(&self.groups);
+        }
+
+        for option in substruct.args.iter().flat_map(|arg| &arg.options) {
+            let result = match () {
+                _ if option.name == "default" => option.expect_flag(),
+                _ if option.name == "from_fn" => option.value_str().map(drop),
+                _ if option.name == "with" => option.value_str().map(drop),
+                _ if option.name == "ty" => option
+                    .value_str()
+                    .and_then(|lit| lit.parse::<syn::Type>())
+                    .map(drop),
+                _ if option.name == "into" => option.expect_flag(),
+                _ if option.name == "map_into" => option.expect_flag(),
+                _ if option.name == "split" => option.parse_list::<SplitField>().map(drop),
+                _ if option.name == "via" => {
+                    option
+                        .value_str()
+                        .and_then(|lit| match lit.value().as_str() {
+                            "try_into" => Ok(()),
+                            _ => Err(syn::Error::new(lit.span(), "expected `via = \"try_into\"`")),
+                        })
+                }
+                _ => Err(option.unknown()),
+            };
+
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        }
+
+        if !substruct.exclusive() {
+            substruct.args.push(SubstructInputArg {
+                docs: Vec::new(),
+                attrs: Vec::new(),
+                expr: Expr::Ident(self.input.ident.clone()),
+                options: Vec::new(),
+            });
+        }
+
+        substruct
+    }
+
+    /// Whether a field flag (e.g. `default`) applies to the struct `name`.
+    fn field_has_flag(&mut self, field: &syn::Field, name: &syn::Ident, flag: &str) -> bool {
+        self.field_input(&field.attrs)
+            .args
+            .iter()
+            .any(|arg| arg.has_flag(flag) && arg.expr.evaluate(name))
+    }
+
+    /// Get the value of a `key = "value"` field option that applies to the
+    /// struct `name`, parsed as a `T`.
+    fn field_str_option<T: Parse>(
+        &mut self,
+        field: &syn::Field,
+        name: &syn::Ident,
+        key: &str,
+    ) -> Option<T> {
+        let input = self.field_input(&field.attrs);
+        let option = input
+            .args
+            .iter()
+            .filter(|arg| arg.expr.evaluate(name))
+            .find_map(|arg| arg.option(key))?;
+
+        match option.value_str().and_then(|lit| lit.parse()) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                self.errors.push(e);
+                None
+            }
+        }
+    }
+
+    /// Get the contents of a `key(...)` field option that applies to the
+    /// struct `name`, parsed as a comma-separated list.
+    fn field_list_option<T: Parse>(
+        &mut self,
+        field: &syn::Field,
+        name: &syn::Ident,
+        key: &str,
+    ) -> Option<Vec<T>> {
+        let input = self.field_input(&field.attrs);
+        let option = input
+            .args
+            .iter()
+            .filter(|arg| arg.expr.evaluate(name))
+            .find_map(|arg| arg.option(key))?;
+
+        match option.parse_list() {
+            Ok(list) => Some(list.into_iter().collect()),
+            Err(e) => {
+                self.errors.push(e);
+                None
+            }
+        }
+    }
+
+    /// Get the names and types of the fields that `field` is split into within
+    /// the struct `name`.
+    ///
+    /// Returns `None` if the field is not split. This must be called before
+    /// the field is filtered.
+    fn split_field(
+        &mut self,
+        field: &syn::Field,
+        name: &syn::Ident,
+    ) -> Option<Vec<(syn::Ident, syn::Type)>> {
+        let split: Vec<SplitField> = self.field_list_option(field, name, "split")?;
+        if split.is_empty() {
+            self.errors.push(syn::Error::new_spanned(
+                field,
+                "split requires at least one field",
+            ));
+        }
+
+        let elems = match &field.ty {
+            syn::Type::Tuple(tuple) if tuple.elems.len() == split.len() => Some(&tuple.elems),
+            _ => None,
+        };
+
+        let mut fields = Vec::with_capacity(split.len());
+        for (index, split) in split.iter().enumerate() {
+            let ty = match (&split.ty, elems) {
+                (Some(ty), _) => ty.clone(),
+                (None, Some(elems)) => elems[index].clone(),
+                (None, None) => {
+                    self.errors.push(syn::Error::new(
+                        split.name.span(),
+                        format_args!(
+                            "unable to infer the type of `{}`, specify it as `{}: Type`",
+                            split.name, split.name
+                        ),
+                    ));
+                    continue;
                 }
             };
 
-            if args.expr.evaluate(name) {
-                attr.meta = args.meta;
-                true
-            } else {
-                false
+            fields.push((split.name.clone(), ty));
+        }
+
+        Some(fields)
+    }
+
+    fn filter_field(&mut self, field: &mut syn::Field, name: &syn::Ident) -> bool {
+        let substruct = self.field_input(&field.attrs);
+
+        let rename = self.field_rename(field, name);
+        if substruct.matching(name).is_none() {
+            if let Some(rename) = rename {
+                self.errors.push(syn::Error::new(
+                    rename.span(),
+                    format_args!("this field is not included in `{name}`"),
+                ));
+            }
+
+            return false;
+        }
+
+        if let Some(ty) = self.field_str_option(field, name, "ty") {
+            field.ty = ty;
+        }
+
+        if let Some((_, ty)) = self.narrowed(field, name) {
+            field.ty = ty.clone();
+        }
+
+        if let Some(rename) = rename {
+            match &field.ident {
+                Some(_) => field.ident = Some(rename),
+                None => self.errors.push(syn::Error::new(
+                    rename.span(),
+                    "fields of a tuple struct cannot be renamed",
+                )),
+            }
+        }
+
+        self.filter_attrs(&mut field.attrs, name);
+
+        if let Some(docs) = substruct.docs(name) {
+            field.attrs.retain(|attr| !is_doc_comment(attr));
+            field.attrs.extend(interpolate_docs(docs, name));
+        }
+        field.attrs.extend(substruct.attrs(name).iter().cloned());
+
+        true
+    }
+
+    /// Get the entry of the `narrow(...)` option on the struct `name` which
+    /// applies to `field`, if there is one.
+    fn narrowed(&self, field: &syn::Field, name: &syn::Ident) -> Option<&(syn::Ident, syn::Type)> {
+        let ident = field.ident.as_ref()?;
+
+        self.args
+            .get(name)?
+            .narrow
+            .iter()
+            .find(|(narrow, _)| narrow == ident)
+    }
+
+    /// Get the new name of a field within the struct `name` from its
+    /// `#[substruct_rename]` attributes.
+    fn field_rename(&mut self, field: &syn::Field, name: &syn::Ident) -> Option<syn::Ident> {
+        // The fields of the parent struct are never renamed.
+        if *name == self.input.ident {
+            return None;
+        }
+
+        let mut rename = None;
+
+        for attr in &field.attrs {
+            if !attr.path().is_ident("substruct_rename") {
+                continue;
+            }
+
+            match attr.parse_args::<SubstructRenameInput>() {
+                Ok(mut args) => {
+                    args.expr.expand_groups(&self.groups);
+                    if args.expr.evaluate(name) {
+                        rename.get_or_insert(args.ident);
+                    }
+                }
+                Err(e) => self.errors.push(e),
+            }
+        }
+
+        rename
+    }
+
+    /// Get the value that a field excluded from the struct `name` is
+    /// initialized with from its `#[substruct_default]` attributes.
+    fn field_default(&mut self, field: &syn::Field, name: &syn::Ident) -> Option<syn::Expr> {
+        let mut value = None;
+
+        for attr in &field.attrs {
+            if !attr.path().is_ident("substruct_default") {
+                continue;
+            }
+
+            match attr.parse_args::<SubstructDefaultInput>() {
+                Ok(mut args) => {
+                    args.expr.expand_groups(&self.groups);
+                    if args.expr.evaluate(name) {
+                        value.get_or_insert(args.value);
+                    }
+                }
+                Err(e) => self.errors.push(e),
+            }
+        }
+
+        value
+    }
+
+    fn filter_attrs(&mut self, attrs: &mut Vec<syn::Attribute>, name: &syn::Ident) {
+        // Whether a `#[substruct_attr(..., substruct_nodoc)]` applies.
+        let mut nodoc = false;
+
+        attrs.retain_mut(|attr| {
+            let path = attr.path();
+
+            if path.is_ident("substruct")
+                || path.is_ident("substruct_rename")
+                || path.is_ident("substruct_default")
+                || path.is_ident("substruct_group")
+            {
+                return false;
+            }
+
+            if !path.is_ident("substruct_attr") {
+                return true;
+            }
+
+            let mut args: SubstructAttrInput = match attr.parse_args() {
+                Ok(args) => args,
+                Err(e) => {
+                    self.errors.push(e);
+                    return false;
+                }
+            };
+
+            args.expr.expand_groups(&self.groups);
+            if !args.expr.evaluate(name) {
+                return false;
+            }
+
+            if matches!(&args.meta, syn::Meta::Path(path) if path.is_ident("substruct_nodoc")) {
+                nodoc = true;
+                return false;
+            }
+
+            attr.meta = match interpolate_meta(&args.meta, name) {
+                Ok(meta) => meta,
+                Err(e) => {
+                    self.errors.push(e);
+                    return false;
+                }
+            };
+            true
+        });
+
+        if nodoc {
+            attrs.retain(|attr| !is_doc_comment(attr));
+        }
+    }
+}
+
+/// Create the fields that `field` is split into. Each of them keeps the
+/// visibility and documentation of the original field.
+fn split_fields(field: &syn::Field, split: Vec<(syn::Ident, syn::Type)>) -> Vec<syn::Field> {
+    split
+        .into_iter()
+        .map(|(ident, ty)| syn::Field {
+            attrs: field
+                .attrs
+                .iter()
+                .filter(|attr| is_doc_comment(attr) || attr.path().is_ident("cfg"))
+                .cloned()
+                .collect(),
+            vis: field.vis.clone(),
+            mutability: syn::FieldMutability::None,
+            ident: Some(ident),
+            colon_token: Some(Default::default()),
+            ty,
+        })
+        .collect()
+}
+
+/// The name of the field containing the `core` struct within the generated
+/// structs that use it.
+fn core_member(core: &syn::Ident) -> syn::Ident {
+    syn::Ident::new(&core.unraw().to_string().to_snake_case(), core.span())
+}
+
+/// Convert each element of the collection `value` using `Into`.
+///
+/// For a `Vec`, the standard library reuses the existing allocation when the
+/// layouts of the element types are compatible.
+fn map_into(value: TokenStream) -> TokenStream {
+    quote::quote! {
+        ::core::iter::Iterator::collect(
+            ::core::iter::Iterator::map(
+                ::core::iter::IntoIterator::into_iter(#value),
+                ::core::convert::Into::into,
+            )
+        )
+    }
+}
+
+/// Get the element type of a collection type with a single type parameter,
+/// such as `Vec<T>`.
+fn element_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let path = match ty {
+        syn::Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+
+    let args = match &path.segments.last()?.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => &args.args,
+        _ => return None,
+    };
+
+    match &args[0] {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+/// Whether `ty` refers to any of the type parameters in `generics`.
+fn uses_type_params(ty: &syn::Type, generics: &syn::Generics) -> bool {
+    fn scan(tokens: TokenStream, params: &[&syn::Ident]) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(ident) => params.contains(&&ident),
+            TokenTree::Group(group) => scan(group.stream(), params),
+            _ => false,
+        })
+    }
+
+    let params: Vec<_> = generics.type_params().map(|param| &param.ident).collect();
+    scan(ty.to_token_stream(), &params)
+}
+
+/// Collect every identifier within `tokens`, including the names of lifetimes.
+fn collect_idents(tokens: TokenStream, idents: &mut IndexSet<syn::Ident>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                idents.insert(ident);
+            }
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            _ => (),
+        }
+    }
+}
+
+/// The identifier of a generic parameter, or the name of a lifetime.
+fn param_ident(param: &syn::GenericParam) -> &syn::Ident {
+    match param {
+        syn::GenericParam::Lifetime(param) => &param.lifetime.ident,
+        syn::GenericParam::Type(param) => &param.ident,
+        syn::GenericParam::Const(param) => &param.ident,
+    }
+}
+
+/// Remove the parameters of `generics` which are not used by any of `fields`,
+/// along with the where predicates that refer to them.
+fn trim_generics(generics: &mut syn::Generics, fields: &syn::Fields) {
+    let mut used = IndexSet::new();
+    for field in fields {
+        collect_idents(field.ty.to_token_stream(), &mut used);
+    }
+
+    let removed: Vec<_> = generics
+        .params
+        .iter()
+        .map(param_ident)
+        .filter(|ident| !used.contains(*ident))
+        .cloned()
+        .collect();
+    if removed.is_empty() {
+        return;
+    }
+
+    generics.params = std::mem::take(&mut generics.params)
+        .into_iter()
+        .filter(|param| !removed.contains(param_ident(param)))
+        .collect();
+
+    // Inline bounds of the remaining parameters (e.g. `'a: 'b`) may also refer
+    // to the removed ones. These are declared on the conversions instead, see
+    // `trimmed_generics`.
+    for param in &mut generics.params {
+        match param {
+            syn::GenericParam::Lifetime(param) => {
+                param.bounds = std::mem::take(&mut param.bounds)
+                    .into_iter()
+                    .filter(|bound| !mentions(bound, &removed))
+                    .collect();
+                if param.bounds.is_empty() {
+                    param.colon_token = None;
+                }
+            }
+            syn::GenericParam::Type(param) => {
+                param.bounds = std::mem::take(&mut param.bounds)
+                    .into_iter()
+                    .filter(|bound| !mentions(bound, &removed))
+                    .collect();
+                if param.bounds.is_empty() {
+                    param.colon_token = None;
+                }
+                if param
+                    .default
+                    .as_ref()
+                    .is_some_and(|ty| mentions(ty, &removed))
+                {
+                    param.eq_token = None;
+                    param.default = None;
+                }
+            }
+            syn::GenericParam::Const(param) => {
+                if param
+                    .default
+                    .as_ref()
+                    .is_some_and(|expr| mentions(expr, &removed))
+                {
+                    param.eq_token = None;
+                    param.default = None;
+                }
+            }
+        }
+    }
+
+    if let Some(where_clause) = &mut generics.where_clause {
+        where_clause.predicates = std::mem::take(&mut where_clause.predicates)
+            .into_iter()
+            .filter(|predicate| !mentions(predicate, &removed))
+            .collect();
+    }
+}
+
+/// Whether `tokens` refer to any of `idents`.
+fn mentions(tokens: &impl ToTokens, idents: &[syn::Ident]) -> bool {
+    let mut found = IndexSet::new();
+    collect_idents(tokens.to_token_stream(), &mut found);
+    idents.iter().any(|ident| found.contains(ident))
+}
+
+/// Add a `PhantomData` field to `fields` for each type or lifetime parameter of
+/// `generics` which is not used by any of the existing fields.
+fn add_phantom_fields(generics: &syn::Generics, vis: &syn::Visibility, fields: &mut syn::Fields) {
+    let mut used = IndexSet::new();
+    for field in fields.iter() {
+        collect_idents(field.ty.to_token_stream(), &mut used);
+    }
+
+    let named = !matches!(fields, syn::Fields::Unnamed(_));
+    let mut phantom = Vec::new();
+    for param in &generics.params {
+        let ty: syn::Type = match param {
+            syn::GenericParam::Lifetime(param) if !used.contains(&param.lifetime.ident) => {
+                let lifetime = &param.lifetime;
+                syn::parse_quote!(::core::marker::PhantomData<&#lifetime ()>)
+            }
+            syn::GenericParam::Type(param) if !used.contains(&param.ident) => {
+                let ident = &param.ident;
+                syn::parse_quote!(::core::marker::PhantomData<#ident>)
+            }
+            _ => continue,
+        };
+
+        // The field is named after the parameter verbatim so that `'a` and `A`
+        // get distinct fields. Using the parameter's span would make
+        // code generated for the struct trip `non_snake_case` despite the
+        // `allow`.
+        let ident = param_ident(param);
+        let ident = quote::format_ident!(
+            "__substruct_phantom_{}",
+            ident.unraw(),
+            span = Span::call_site()
+        );
+        phantom.push(syn::Field {
+            attrs: vec![syn::parse_quote!(#[allow(non_snake_case)])],
+            vis: vis.clone(),
+            mutability: syn::FieldMutability::None,
+            ident: named.then_some(ident),
+            colon_token: named.then(Default::default),
+            ty,
+        });
+    }
+
+    if phantom.is_empty() {
+        return;
+    }
+
+    match fields {
+        syn::Fields::Named(fields) => fields.named.extend(phantom),
+        syn::Fields::Unnamed(fields) => fields.unnamed.extend(phantom),
+        syn::Fields::Unit => {
+            *fields = syn::Fields::Named(syn::FieldsNamed {
+                brace_token: Default::default(),
+                named: phantom.into_iter().collect(),
+            })
+        }
+    }
+}
+
+/// The generic parameters of `parent` which are missing from `child`, along
+/// with the where predicates of `parent` that refer to them.
+fn trimmed_generics(parent: &syn::Generics, child: &syn::Generics) -> syn::Generics {
+    let declared: Vec<_> = child.params.iter().map(param_ident).collect();
+    let params: Punctuated<_, syn::Token![,]> = parent
+        .params
+        .iter()
+        .filter(|param| !declared.contains(&param_ident(param)))
+        .cloned()
+        .collect();
+    let removed: Vec<_> = params.iter().map(param_ident).cloned().collect();
+
+    let mut trimmed = syn::Generics::default();
+    if params.is_empty() {
+        return trimmed;
+    }
+
+    // Inline bounds on the parameters that the child kept were removed from
+    // the child if they refer to a trimmed parameter, so they are turned into
+    // where predicates here.
+    let mut predicates: Vec<syn::WherePredicate> = Vec::new();
+    for param in &parent.params {
+        if !declared.contains(&param_ident(param)) {
+            continue;
+        }
+
+        match param {
+            syn::GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                predicates.extend(
+                    param
+                        .bounds
+                        .iter()
+                        .filter(|bound| mentions(bound, &removed))
+                        .map(|bound| -> syn::WherePredicate {
+                            syn::parse_quote!(#lifetime: #bound)
+                        }),
+                );
+            }
+            syn::GenericParam::Type(param) => {
+                let ident = &param.ident;
+                predicates.extend(
+                    param
+                        .bounds
+                        .iter()
+                        .filter(|bound| mentions(bound, &removed))
+                        .map(|bound| -> syn::WherePredicate { syn::parse_quote!(#ident: #bound) }),
+                );
+            }
+            syn::GenericParam::Const(_) => (),
+        }
+    }
+
+    if let Some(where_clause) = &parent.where_clause {
+        predicates.extend(
+            where_clause
+                .predicates
+                .iter()
+                .filter(|predicate| mentions(predicate, &removed))
+                .cloned(),
+        );
+    }
+
+    if !predicates.is_empty() {
+        trimmed.make_where_clause().predicates.extend(predicates);
+    }
+
+    trimmed.lt_token = Some(Default::default());
+    trimmed.gt_token = Some(Default::default());
+    trimmed.params = params;
+    trimmed
+}
+
+/// Combine the parameters and where predicates of two sets of generics,
+/// keeping lifetimes before the other parameters.
+fn merge_generics(first: &syn::Generics, second: &syn::Generics) -> syn::Generics {
+    if second.params.is_empty() {
+        return first.clone();
+    }
+
+    let params = first.params.iter().chain(&second.params);
+    let lifetimes = params
+        .clone()
+        .filter(|param| matches!(param, syn::GenericParam::Lifetime(_)));
+    let others = params.filter(|param| !matches!(param, syn::GenericParam::Lifetime(_)));
+
+    let mut merged = syn::Generics {
+        lt_token: Some(Default::default()),
+        params: lifetimes.chain(others).cloned().collect(),
+        gt_token: Some(Default::default()),
+        where_clause: None,
+    };
+    let predicates = first
+        .where_clause
+        .iter()
+        .chain(&second.where_clause)
+        .flat_map(|where_clause| where_clause.predicates.iter().cloned());
+    merged.make_where_clause().predicates.extend(predicates);
+    if merged.where_clause.as_ref().unwrap().predicates.is_empty() {
+        merged.where_clause = None;
+    }
+    merged
+}
+
+/// Add `bounds` to the where clause of `generics`.
+fn with_bounds(generics: &syn::Generics, bounds: Vec<syn::WherePredicate>) -> syn::Generics {
+    let mut generics = generics.clone();
+    if !bounds.is_empty() {
+        generics.make_where_clause().predicates.extend(bounds);
+    }
+    generics
+}
+
+/// The `#[cfg]` attributes within `attrs`.
+fn cfg_attrs(attrs: &[syn::Attribute]) -> Vec<syn::Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .cloned()
+        .collect()
+}
+
+/// The attributes to put on each item emitted alongside the generated struct
+/// `input`.
+///
+/// The items get the `#[cfg]`s of the struct so that they are compiled out
+/// along with it. If the struct or any of its fields are `#[deprecated]` then
+/// using them within the items is allowed.
+fn emitted_attrs(input: &syn::DeriveInput) -> TokenStream {
+    let cfgs = cfg_attrs(&input.attrs);
+    let deprecated = is_deprecated(&input.attrs)
+        || match &input.data {
+            syn::Data::Struct(data) => data.fields.iter().any(|field| is_deprecated(&field.attrs)),
+            syn::Data::Enum(data) => data.variants.iter().any(|variant| {
+                is_deprecated(&variant.attrs)
+                    || variant
+                        .fields
+                        .iter()
+                        .any(|field| is_deprecated(&field.attrs))
+            }),
+            syn::Data::Union(data) => data
+                .fields
+                .named
+                .iter()
+                .any(|field| is_deprecated(&field.attrs)),
+        };
+    let allow = deprecated.then(|| quote::quote!(#[allow(deprecated)]));
+
+    quote::quote! {
+        #( #cfgs )*
+        #allow
+    }
+}
+
+/// Whether `path` refers to the derive macro `name`, ignoring any leading path
+/// segments.
+fn is_derive(path: &syn::Path, name: &str) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|segment| segment.ident == name)
+}
+
+/// Whether `attrs` contains a `#[derive(...)]` for the derive macro `name`.
+fn has_derive(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .any(|path| is_derive(&path, name))
+}
+
+/// The name of the derive macro referred to by `path`.
+fn derive_name(path: &syn::Path) -> String {
+    match path.segments.last() {
+        Some(segment) => segment.ident.to_string(),
+        None => String::new(),
+    }
+}
+
+/// Remove the derives within `#[derive(...)]` attributes for which `keep`
+/// returns false. Derive attributes which end up empty are removed entirely.
+fn retain_derives(
+    attrs: &mut Vec<syn::Attribute>,
+    mut keep: impl FnMut(&syn::Path) -> bool,
+) -> syn::Result<()> {
+    let mut result = Ok(());
+
+    attrs.retain_mut(|attr| {
+        if !attr.path().is_ident("derive") {
+            return true;
+        }
+
+        let derives =
+            match attr.parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated) {
+                Ok(derives) => derives,
+                Err(e) => {
+                    result = Err(e);
+                    return true;
+                }
+            };
+
+        let derives: Punctuated<_, syn::Token![,]> =
+            derives.into_iter().filter(|path| keep(path)).collect();
+        if derives.is_empty() {
+            return false;
+        }
+
+        *attr = syn::parse_quote!(#[derive(#derives)]);
+        true
+    });
+
+    result
+}
+
+/// Whether `attr` is a doc comment (`/// ...` or `#[doc = "..."]`).
+///
+/// Other doc attributes, such as `#[doc(hidden)]`, are not documentation text
+/// and should be kept when the documentation is overridden.
+fn is_doc_comment(attr: &syn::Attribute) -> bool {
+    attr.path().is_ident("doc") && matches!(attr.meta, syn::Meta::NameValue(_))
+}
+
+/// The key of the `#[substruct]` attributes within `attrs` in
+/// `Emitter::field_inputs`.
+fn field_input_key(attrs: &[syn::Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("substruct"))
+        .map(|attr| attr.to_token_stream().to_string())
+        .collect()
+}
+
+/// Whether any of `attrs` is a `#[deprecated]` attribute.
+fn is_deprecated(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("deprecated"))
+}
+
+/// Replace `{struct}` within doc comment overrides with the name of the
+/// struct they are being emitted for.
+fn interpolate_docs(docs: &[syn::Attribute], name: &syn::Ident) -> Vec<syn::Attribute> {
+    let name = name.unraw().to_string();
+
+    docs.iter()
+        .cloned()
+        .map(|mut attr| {
+            if let syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit),
+                        ..
+                    }),
+                ..
+            }) = &mut attr.meta
+            {
+                *lit = syn::LitStr::new(&lit.value().replace("{struct}", &name), lit.span());
             }
+
+            attr
         })
+        .collect()
+}
+
+/// Replace `{struct}` with `name` within the string literals of an attribute
+/// from `#[substruct_attr]`.
+fn interpolate_meta(meta: &syn::Meta, name: &syn::Ident) -> syn::Result<syn::Meta> {
+    fn interpolate(tokens: TokenStream, name: &str) -> TokenStream {
+        tokens
+            .into_iter()
+            .map(|tt| match tt {
+                TokenTree::Group(group) => {
+                    let mut new = proc_macro2::Group::new(
+                        group.delimiter(),
+                        interpolate(group.stream(), name),
+                    );
+                    new.set_span(group.span());
+                    TokenTree::Group(new)
+                }
+                TokenTree::Literal(lit) => match syn::Lit::new(lit.clone()) {
+                    syn::Lit::Str(lit) if lit.value().contains("{struct}") => {
+                        let value = lit.value().replace("{struct}", name);
+                        syn::LitStr::new(&value, lit.span()).token()
+                    }
+                    _ => lit,
+                }
+                .into(),
+                tt => tt,
+            })
+            .collect()
     }
+
+    let name = name.unraw().to_string();
+    syn::parse2(interpolate(meta.to_token_stream(), &name))
 }
 
 pub fn expand(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
@@ -383,6 +5430,51 @@ pub fn expand(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream>
     Ok(Emitter::from_input(&input, args)?.emit())
 }
 
+/// A field of the parent struct which is included in a generated struct.
+struct IncludedField {
+    /// A function used to compute the parent field from the child field and
+    /// the excluded fields.
+    from_fn: Option<syn::Path>,
+
+    /// A module containing `from_parent` and `into_parent` functions used to
+    /// transform the field value in each direction.
+    with: Option<syn::Path>,
+
+    /// Whether the field is converted using `Into` in each direction.
+    into: bool,
+
+    /// Whether each element of the field is converted using `Into` in each
+    /// direction.
+    map_into: bool,
+
+    /// The name of the field within the generated struct, which may differ
+    /// from the parent if it was renamed.
+    ident: Option<syn::Ident>,
+
+    /// The fields that this field is split into within the generated struct.
+    split: Option<Vec<syn::Ident>>,
+
+    /// The variant of the conversion error used when the field is converted
+    /// from the parent using `TryInto`.
+    try_into: Option<syn::Ident>,
+
+    /// The `#[cfg]` attributes on the field.
+    cfg: Vec<syn::Attribute>,
+}
+
+impl IncludedField {
+    /// Whether the value of the field is transformed when converting between
+    /// the parent and the generated struct.
+    fn is_transformed(&self) -> bool {
+        self.from_fn.is_some()
+            || self.with.is_some()
+            || self.into
+            || self.map_into
+            || self.split.is_some()
+            || self.try_into.is_some()
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
 enum IdentOrIndex {
     Ident(syn::Ident),
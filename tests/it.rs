@@ -1,3 +1,5 @@
+use std::marker::PhantomData;
+
 use substruct::substruct;
 
 #[test]
@@ -11,6 +13,22 @@ fn test_convert_tuple() {
     assert!(matches!(a, A(5, 32)))
 }
 
+#[test]
+fn test_convert_tuple_interleaved() {
+    #[substruct(B)]
+    #[derive(Debug, PartialEq)]
+    struct A(
+        #[substruct(B)] pub i32,
+        pub String,
+        #[substruct(B)] pub i64,
+        pub bool,
+    );
+
+    let a = B(1, 2).into_a("text".into(), true);
+    assert_eq!(a, A(1, "text".into(), 2, true));
+    assert_eq!(B::from(a), B(1, 2));
+}
+
 #[test]
 fn test_convert_normal() {
     #[substruct(B)]
@@ -31,3 +49,2739 @@ fn test_convert_normal() {
         }
     ));
 }
+
+#[test]
+fn test_method_name_acronyms() {
+    #[substruct(SubHTTPConfig)]
+    struct HTTPConfig {
+        #[substruct(SubHTTPConfig)]
+        pub port: u16,
+    }
+
+    #[substruct(SubXMLHttpRequest)]
+    struct XMLHttpRequest {
+        #[substruct(SubXMLHttpRequest)]
+        pub body: u32,
+    }
+
+    let config = SubHTTPConfig { port: 80 }.into_http_config();
+    let request = SubXMLHttpRequest { body: 5 }.into_xml_http_request();
+
+    assert_eq!(config.port, 80);
+    assert_eq!(request.body, 5);
+}
+
+#[test]
+fn test_method_case_lowercase() {
+    #[substruct(SubHTTPConfig, method_case = "lowercase")]
+    struct HTTPConfig {
+        #[substruct(SubHTTPConfig)]
+        pub port: u16,
+    }
+
+    let config = SubHTTPConfig { port: 80 }.into_httpconfig();
+
+    assert_eq!(config.port, 80);
+}
+
+#[test]
+fn test_method_name() {
+    #[substruct(Body, method = "into_request")]
+    #[derive(Debug, PartialEq)]
+    struct HTTPRequest {
+        #[substruct(Body)]
+        pub body: Vec<u8>,
+        pub path: String,
+    }
+
+    let request = Body { body: vec![1] }.into_request("/".into());
+    assert_eq!(
+        request,
+        HTTPRequest {
+            body: vec![1],
+            path: "/".into()
+        }
+    );
+}
+
+#[test]
+fn test_generic_order() {
+    #[substruct(Swapped, generic_order(U, T))]
+    #[derive(Debug, PartialEq)]
+    struct Pair<T, U> {
+        #[substruct(Swapped)]
+        pub first: T,
+        #[substruct(Swapped)]
+        pub second: U,
+    }
+
+    let swapped: Swapped<&str, u32> = Swapped {
+        first: 5u32,
+        second: "five",
+    };
+    let pair: Pair<u32, &str> = swapped.into();
+
+    assert_eq!(
+        pair,
+        Pair {
+            first: 5,
+            second: "five"
+        }
+    );
+}
+
+#[test]
+fn test_where_predicates() {
+    #[substruct(Wire, where = "T: serde::Serialize", attr(derive(serde::Serialize)))]
+    #[derive(Debug, PartialEq)]
+    struct Message<T> {
+        #[substruct(Wire)]
+        pub payload: T,
+        pub retries: u32,
+    }
+
+    let wire = Wire { payload: 5u8 };
+    assert_eq!(serde_json::to_string(&wire).unwrap(), r#"{"payload":5}"#);
+    assert_eq!(
+        wire.into_message(1),
+        Message {
+            payload: 5,
+            retries: 1
+        }
+    );
+}
+
+#[test]
+fn test_trim_generics() {
+    #[substruct(Summary, trim_generics, Header, trim_generics)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Document<'a, T: Clone, U>
+    where
+        U: Default,
+    {
+        #[substruct(Summary, Header)]
+        pub title: String,
+        #[substruct(Summary)]
+        pub meta: T,
+        pub body: &'a str,
+        #[substruct(Header, default)]
+        pub extra: U,
+    }
+
+    let summary = Summary {
+        title: "title".into(),
+        meta: 1u8,
+    };
+    let doc = summary.clone().into_document("body", 5u32);
+    assert_eq!(doc.body, "body");
+    assert_eq!(Summary::from(doc.clone()), summary);
+
+    let header = Header::from(doc);
+    let doc: Document<u8, u32> = header.into_document(3, "text");
+    assert_eq!(doc.extra, 0);
+}
+
+#[test]
+fn test_trim_generics_lifetime_only() {
+    #[substruct(Name, trim_generics, Value, trim_generics)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Entry<'a: 'b, 'b, T: Clone + 'a>
+    where
+        Option<T>: Clone,
+    {
+        #[substruct(Name)]
+        pub name: &'a str,
+        pub label: &'b str,
+        #[substruct(Value)]
+        pub value: Option<T>,
+    }
+
+    let entry = Entry {
+        name: "name",
+        label: "label",
+        value: Some(5u8),
+    };
+
+    let name = Name::from(entry.clone());
+    assert_eq!(name, Name { name: "name" });
+    assert_eq!(name.into_entry("label", Some(5u8)), entry);
+
+    let value = Value::from(entry.clone());
+    assert_eq!(value.into_entry("name", "label"), entry);
+}
+
+#[test]
+fn test_phantom() {
+    #[substruct(NoLifetime, phantom)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct UsesLifetime<'a, T> {
+        #[substruct(NoLifetime)]
+        pub name: String,
+        pub text: &'a str,
+        pub value: T,
+    }
+
+    #[substruct(Empty, phantom)]
+    struct Pair<T>(pub T, #[substruct(Empty)] pub u32);
+
+    let name = NoLifetime {
+        name: "name".into(),
+        __substruct_phantom_a: PhantomData,
+        __substruct_phantom_T: PhantomData,
+    };
+    let parent = name.clone().into_uses_lifetime("text", 5);
+    assert_eq!(parent.text, "text");
+    assert_eq!(NoLifetime::from(parent), name);
+
+    let pair = Empty::<bool>(1, PhantomData).into_pair(true);
+    assert!(pair.0);
+    assert_eq!(Empty::from(pair).0, 1);
+}
+
+#[test]
+fn test_phantom_same_name() {
+    #[substruct(Id, phantom)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Labeled<'a, A> {
+        #[substruct(Id)]
+        pub id: u32,
+        pub label: &'a str,
+        pub value: A,
+    }
+
+    let id = Id {
+        id: 1,
+        __substruct_phantom_a: PhantomData,
+        __substruct_phantom_A: PhantomData,
+    };
+    let parent = id.clone().into_labeled("label", 5u8);
+    assert_eq!(parent.label, "label");
+    assert_eq!(Id::from(parent), id);
+}
+
+#[test]
+fn test_summary() {
+    use schemars::{schema_for, JsonSchema};
+
+    /// Documentation that the summary is appended to.
+    #[substruct(B, C, summary)]
+    #[derive(JsonSchema)]
+    struct A {
+        #[substruct(B, C)]
+        pub x: u32,
+        #[substruct(C)]
+        pub y: u32,
+    }
+
+    let a = C { x: 1, y: 2 }.into_a();
+    let b = B::from(a);
+
+    assert_eq!(b.x, 1);
+
+    // The schema description is taken from the doc comment of the parent, with
+    // the lines of each paragraph joined by spaces.
+    let schema = serde_json::to_value(schema_for!(A)).unwrap();
+    assert_eq!(
+        schema["description"],
+        "Documentation that the summary is appended to.\n\n\
+         # Generated Structs \
+         The following structs are generated from this one by `#[substruct]`: \
+         - [`B`] (1 field) \
+         - [`C`] (2 fields)"
+    );
+}
+
+#[test]
+fn test_field_default() {
+    #[substruct(B)]
+    #[derive(Debug, PartialEq)]
+    struct A {
+        #[substruct(B)]
+        pub x: u32,
+        pub y: u32,
+        #[substruct(B, default)]
+        pub z: Vec<u32>,
+    }
+
+    let a = B { x: 1 }.into_a(2);
+
+    assert_eq!(
+        a,
+        A {
+            x: 1,
+            y: 2,
+            z: Vec::new()
+        }
+    );
+}
+
+#[test]
+fn test_substruct_default() {
+    #[substruct(B, C, D)]
+    #[derive(Debug, PartialEq)]
+    struct A {
+        #[substruct(B, C, D)]
+        pub x: u32,
+        #[substruct(D)]
+        #[substruct_default(any(B, C), 5)]
+        pub y: u32,
+        #[substruct_default(B, vec![1, 2])]
+        #[substruct_default(all(), Vec::new())]
+        pub z: Vec<u32>,
+    }
+
+    assert_eq!(
+        B { x: 1 }.into_a(),
+        A {
+            x: 1,
+            y: 5,
+            z: vec![1, 2]
+        }
+    );
+    assert_eq!(
+        A::from(C { x: 1 }),
+        A {
+            x: 1,
+            y: 5,
+            z: Vec::new()
+        }
+    );
+    assert_eq!(
+        D { x: 1, y: 2 }.into_a(),
+        A {
+            x: 1,
+            y: 2,
+            z: Vec::new()
+        }
+    );
+}
+
+#[test]
+#[deny(deprecated)]
+fn test_arg_attrs() {
+    #[substruct(
+        #[deprecated = "use `Current` instead"]
+        Old,
+        Current,
+        #[cfg(any())]
+        Never,
+    )]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Dto {
+        #[substruct(Old, #[deprecated] Current)]
+        pub id: u32,
+        #[substruct(Current, Never)]
+        pub name: String,
+    }
+
+    // This would conflict with `Never` or its impls if they weren't compiled
+    // out.
+    struct Never;
+    let _never = Never;
+
+    let dto = Dto {
+        id: 1,
+        name: "name".into(),
+    };
+
+    #[allow(deprecated)]
+    let old = Old::from(dto.clone());
+    #[allow(deprecated)]
+    let current = Current::from(dto.clone());
+
+    assert_eq!(old.into_dto("name".into()), dto);
+    assert_eq!(current.into_dto(), dto);
+}
+
+#[test]
+fn test_method_alias() {
+    #[substruct(B, method_alias = "to_full", method_alias = "expand")]
+    struct A {
+        #[substruct(B)]
+        pub x: u32,
+    }
+
+    let a = B { x: 3 }.into_a();
+
+    assert_eq!(a.x, 3);
+}
+
+#[test]
+fn test_conversion_doc() {
+    #[substruct(B, conversion_doc = "Wandelt `self` in [`{parent}`] um.")]
+    #[derive(Debug, PartialEq)]
+    #[allow(dead_code)]
+    enum A {
+        #[substruct(B)]
+        X(u32),
+        Y,
+    }
+
+    #[substruct(D, conversion_doc = "Convertit `self` en [`{parent}`].")]
+    struct C {
+        #[substruct(D)]
+        pub x: u32,
+    }
+
+    assert_eq!(B::X(3).into_a(), A::X(3));
+    assert_eq!(D { x: 3 }.into_c().x, 3);
+}
+
+#[test]
+fn test_convert_tuple_subsequence() {
+    #[substruct(B, C)]
+    #[derive(Debug, PartialEq)]
+    struct A(
+        #[substruct(B)] pub u8,
+        #[substruct(C)] pub u16,
+        #[substruct(B, C)] pub u32,
+    );
+
+    let b = B(1, 3);
+    let c = C::from(A(1, 2, 3));
+
+    assert_eq!(b.into_a(2), A(1, 2, 3));
+    assert_eq!(c, C(2, 3));
+}
+
+#[test]
+fn test_impl_marker() {
+    trait Subset {}
+    trait Marker {}
+
+    fn is_subset<T: Subset + Marker>() {}
+
+    #[substruct(B, impl_marker = "Subset", impl_marker = "Marker")]
+    struct A<T> {
+        #[substruct(B)]
+        pub x: T,
+    }
+
+    is_subset::<B<u32>>();
+}
+
+#[test]
+fn test_field_from_fn() {
+    fn scale(value: u32, factor: &u32, _label: &String) -> u32 {
+        value * factor
+    }
+
+    #[substruct(B)]
+    #[derive(Debug, PartialEq)]
+    struct A {
+        #[substruct(B, from_fn = "scale")]
+        pub value: u32,
+        pub factor: u32,
+        pub label: String,
+    }
+
+    let a = B { value: 3 }.into_a(4, "label".into());
+
+    assert_eq!(
+        a,
+        A {
+            value: 12,
+            factor: 4,
+            label: "label".into()
+        }
+    );
+}
+
+#[test]
+fn test_field_with_negation() {
+    mod negate {
+        pub fn from_parent(value: bool) -> bool {
+            !value
+        }
+
+        pub fn into_parent(value: bool) -> bool {
+            !value
+        }
+    }
+
+    #[substruct(Inverted)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Settings {
+        #[substruct(Inverted, with = "negate")]
+        pub enabled: bool,
+        #[substruct(Inverted)]
+        pub level: u8,
+    }
+
+    let settings = Settings {
+        enabled: true,
+        level: 3,
+    };
+    let inverted = Inverted::from(settings.clone());
+
+    assert!(!inverted.enabled);
+    assert_eq!(inverted.level, 3);
+    assert_eq!(Settings::from(inverted), settings);
+}
+
+#[test]
+fn test_bool_literals() {
+    #[substruct(A, B)]
+    #[derive(Debug, PartialEq)]
+    struct Parent {
+        #[substruct(true)]
+        pub always: u32,
+        #[substruct(false)]
+        pub never: u32,
+        #[substruct(all(B, not(false)))]
+        #[substruct_attr(true, allow(dead_code))]
+        pub only_b: u32,
+    }
+
+    let a = A { always: 1 };
+    let b = B {
+        always: 2,
+        only_b: 3,
+    };
+    assert_eq!(
+        a.into_parent(4, 5),
+        Parent {
+            always: 1,
+            never: 4,
+            only_b: 5
+        }
+    );
+    assert_eq!(b.into_parent(6).never, 6);
+}
+
+#[test]
+fn test_all_except() {
+    #[substruct(A, B, C)]
+    #[derive(Debug, PartialEq)]
+    struct Parent {
+        #[substruct(all_except(A, B))]
+        #[substruct_attr(all_except(C), allow(dead_code))]
+        pub only_c: u32,
+        #[substruct(all_except(C))]
+        pub not_c: u32,
+    }
+
+    let c = C { only_c: 1 };
+    let a = A { not_c: 2 };
+    assert_eq!(
+        a.into_parent(c.only_c),
+        Parent {
+            only_c: 1,
+            not_c: 2
+        }
+    );
+    assert_eq!(B { not_c: 3 }.into_parent(4).only_c, 4);
+}
+
+#[test]
+fn test_local_scope_resolution() {
+    // A local item that shadows the prelude `From` trait. Generated code must
+    // not be affected by it.
+    #[allow(dead_code)]
+    trait From {}
+
+    mod transform {
+        pub fn from_parent(value: u32) -> u32 {
+            value + 1
+        }
+
+        pub fn into_parent(value: u32) -> u32 {
+            value - 1
+        }
+    }
+
+    trait Marker {}
+
+    #[substruct(B, impl_marker = "Marker", method_alias = "full")]
+    #[derive(Clone, Debug, PartialEq)]
+    struct A {
+        #[substruct(B, with = "transform")]
+        pub x: u32,
+        #[substruct(B, default)]
+        pub y: Vec<u32>,
+    }
+
+    fn assert_marker<T: Marker>(_: &T) {}
+
+    let a = A { x: 1, y: vec![] };
+    let b: B = a.clone().into();
+
+    assert_marker(&b);
+    assert_eq!(b.x, 2);
+    assert_eq!(A::from(b), a);
+}
+
+#[test]
+fn test_bytemuck_pod() {
+    use bytemuck::{Pod, Zeroable};
+
+    #[substruct(Position)]
+    #[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+    #[repr(C)]
+    struct Vertex {
+        #[substruct(Position)]
+        pub x: f32,
+        #[substruct(Position)]
+        pub y: f32,
+        pub color: u32,
+    }
+
+    let position = Position { x: 1.0, y: 2.0 };
+    let bytes = bytemuck::bytes_of(&position);
+
+    assert_eq!(bytes.len(), 8);
+    assert_eq!(*bytemuck::from_bytes::<Position>(bytes), position);
+    assert_eq!(
+        bytemuck::bytes_of(&position.into_vertex(7)).len(),
+        std::mem::size_of::<Vertex>()
+    );
+}
+
+#[test]
+fn test_validate_parent() {
+    #[derive(Debug, PartialEq)]
+    struct RangeError;
+
+    fn check(range: &Range) -> Result<(), RangeError> {
+        match range.start <= range.end {
+            true => Ok(()),
+            false => Err(RangeError),
+        }
+    }
+
+    #[substruct(
+        Start,
+        validate_parent = "check",
+        validate_error = "RangeError",
+        Both,
+        validate_parent = "check",
+        validate_error = "RangeError"
+    )]
+    #[derive(Debug, PartialEq)]
+    struct Range {
+        #[substruct(Start, Both)]
+        pub start: u32,
+        #[substruct(Both)]
+        pub end: u32,
+    }
+
+    assert_eq!(
+        Start { start: 1 }.into_range(5),
+        Ok(Range { start: 1, end: 5 })
+    );
+    assert_eq!(Start { start: 6 }.into_range(5), Err(RangeError));
+    assert_eq!(Range::try_from(Both { start: 2, end: 1 }), Err(RangeError));
+}
+
+#[test]
+fn test_checked() {
+    #[derive(Debug, PartialEq)]
+    struct RangeError;
+
+    #[substruct(Start, checked, validate_error = "RangeError")]
+    #[derive(Debug, PartialEq)]
+    struct Range {
+        #[substruct(Start)]
+        pub start: u32,
+        pub end: u32,
+    }
+
+    impl Range {
+        fn validate(&self) -> Result<(), RangeError> {
+            match self.start <= self.end {
+                true => Ok(()),
+                false => Err(RangeError),
+            }
+        }
+    }
+
+    assert_eq!(
+        Start { start: 1 }.into_range(5),
+        Ok(Range { start: 1, end: 5 })
+    );
+    assert_eq!(Start { start: 6 }.into_range(5), Err(RangeError));
+}
+
+#[test]
+fn test_vis() {
+    mod inner {
+        use substruct::substruct;
+
+        #[substruct(Dto, vis = "pub(super)")]
+        #[derive(Debug, PartialEq)]
+        pub struct Record {
+            #[substruct(Dto)]
+            pub id: u32,
+            pub name: String,
+        }
+    }
+
+    let dto = inner::Dto { id: 4 };
+    assert_eq!(
+        dto.into_record("test".into()),
+        inner::Record {
+            id: 4,
+            name: "test".into()
+        }
+    );
+}
+
+#[test]
+fn test_profiles() {
+    #[substruct(profiles(Profile(FileSource, EnvVars, CliArgs)))]
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct Config {
+        pub host: String,
+        pub port: u16,
+        pub verbose: bool,
+    }
+
+    let base = Config {
+        host: "localhost".into(),
+        port: 80,
+        verbose: false,
+    };
+    let file = FileSource {
+        host: Some("example.com".into()),
+        port: Some(8080),
+        verbose: None,
+    };
+    let env = EnvVars {
+        port: Some(9090),
+        ..Default::default()
+    };
+    let cli = CliArgs {
+        verbose: Some(true),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        Profile::ALL,
+        &[Profile::FileSource, Profile::EnvVars, Profile::CliArgs]
+    );
+    assert_eq!(
+        Profile::merge_all(base, file, env, cli),
+        Config {
+            host: "example.com".into(),
+            port: 9090,
+            verbose: true,
+        }
+    );
+}
+
+#[test]
+fn test_chain_to() {
+    #[substruct(Mid, chain_to = Leaf)]
+    #[substruct_attr(Mid, substruct(Leaf))]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Full {
+        #[substruct(Mid)]
+        #[substruct_attr(Mid, substruct(Leaf))]
+        pub a: u32,
+        #[substruct(Mid)]
+        pub b: u32,
+        pub c: u32,
+    }
+
+    let full = Full { a: 1, b: 2, c: 3 };
+
+    assert_eq!(full.clone().into_leaf(), Leaf { a: 1 });
+    assert_eq!(Leaf::from(full), Leaf { a: 1 });
+    assert_eq!(Mid { a: 1, b: 2 }.into_full(3).a, 1);
+}
+
+#[test]
+fn test_eq_with() {
+    fn nan_eq(a: &f32, b: &f32) -> bool {
+        (a.is_nan() && b.is_nan()) || a == b
+    }
+
+    #[substruct(Sample, eq_with(value = "nan_eq"))]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Measurement {
+        #[substruct(Sample)]
+        pub value: f32,
+        #[substruct(Sample)]
+        pub count: u32,
+        pub label: &'static str,
+    }
+
+    let a = Sample {
+        value: f32::NAN,
+        count: 1,
+    };
+
+    assert_eq!(a, a.clone());
+    assert_ne!(
+        a,
+        Sample {
+            count: 2,
+            ..a.clone()
+        }
+    );
+
+    let m = a.clone().into_measurement("m");
+    assert_ne!(m, m.clone());
+}
+
+#[test]
+fn test_non_exhaustive() {
+    #[substruct(InternalDto, PublicDto)]
+    #[non_exhaustive]
+    #[substruct_attr(PublicDto, non_exhaustive)]
+    #[derive(Debug, PartialEq)]
+    struct Event {
+        #[substruct(InternalDto, PublicDto)]
+        pub id: u32,
+        pub payload: String,
+    }
+
+    let event = InternalDto { id: 1 }.into_event("text".into());
+    assert_eq!(PublicDto::from(event).id, 1);
+}
+
+#[test]
+fn test_serde_flatten() {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    #[substruct(Public, Minimal)]
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        #[substruct(Public, Minimal)]
+        pub id: u32,
+        #[substruct(Public)]
+        #[serde(flatten)]
+        pub extra: HashMap<String, Value>,
+        pub secret: String,
+    }
+
+    let json = r#"{"id":1,"color":"red","size":3}"#;
+
+    let public: Public = serde_json::from_str(json).unwrap();
+    assert_eq!(public.extra.len(), 2);
+
+    let record = public.clone().into_record("hunter2".into());
+    assert_eq!(record.extra["color"], "red");
+    assert_eq!(Public::from(record.clone()), public);
+
+    let value = serde_json::to_value(&record).unwrap();
+    assert_eq!(value["size"], 3);
+    assert_eq!(value["secret"], "hunter2");
+
+    // Minimal does not include the flattened field, so it has to be provided
+    // when converting back to the parent.
+    let minimal: Minimal = serde_json::from_str(json).unwrap();
+    let record = minimal.into_record(public.extra, "hunter2".into());
+    assert_eq!(record.extra["size"], 3);
+}
+
+#[test]
+fn test_constructor() {
+    mod inner {
+        use substruct::substruct;
+
+        #[substruct(Named, constructor)]
+        pub struct Parent {
+            #[substruct(Named)]
+            a: u32,
+            #[substruct(Named)]
+            b: &'static str,
+            c: bool,
+        }
+
+        #[substruct(Pair, constructor)]
+        pub struct Triple(#[substruct(Pair)] pub u8, pub u8, #[substruct(Pair)] pub u8);
+
+        impl Named {
+            pub fn a(&self) -> u32 {
+                self.a
+            }
+        }
+
+        impl Parent {
+            pub fn describe(&self) -> String {
+                format!("{} {} {}", self.a, self.b, self.c)
+            }
+        }
+    }
+
+    let named = inner::Named::new(1, "b");
+    assert_eq!(named.a(), 1);
+    assert_eq!(named.into_parent(true).describe(), "1 b true");
+
+    let pair = inner::Pair::new(1, 3);
+    let triple = pair.into_triple(2);
+    assert_eq!((triple.0, triple.1, triple.2), (1, 2, 3));
+}
+
+#[test]
+fn test_builder() {
+    #[substruct(Endpoint, builder)]
+    #[derive(Debug, PartialEq)]
+    struct Server<T> {
+        #[substruct(Endpoint)]
+        pub host: String,
+        #[substruct(Endpoint)]
+        pub port: T,
+        pub workers: usize,
+    }
+
+    let endpoint = Endpoint::builder()
+        .port(8080u16)
+        .host("localhost".into())
+        .build();
+    assert_eq!(
+        endpoint,
+        Ok(Endpoint {
+            host: "localhost".into(),
+            port: 8080
+        })
+    );
+    assert_eq!(
+        EndpointBuilder::<u16>::default()
+            .host("localhost".into())
+            .build(),
+        Err("port")
+    );
+
+    let server = endpoint.unwrap().into_server(4);
+    assert_eq!(server.workers, 4);
+}
+
+#[test]
+fn test_propagate_derives() {
+    use serde::Serialize;
+
+    #[substruct(Small, propagate_derives(Clone, core::fmt::Debug))]
+    #[derive(Clone, Debug, Default, Serialize)]
+    // This would conflict with the parent's derive if it was propagated.
+    #[substruct_attr(Small, derive(Default))]
+    struct Large {
+        #[substruct(Small)]
+        pub a: u32,
+        pub b: u32,
+    }
+
+    let small = Small::default();
+    assert_eq!(format!("{:?}", small.clone()), "Small { a: 0 }");
+
+    let large = small.into_large(Large::default().b);
+    assert_eq!(serde_json::to_string(&large).unwrap(), r#"{"a":0,"b":0}"#);
+}
+
+#[test]
+fn test_type_alias_fields() {
+    type Pair<T> = (T, T);
+    type Name = String;
+    type Lookup<K, V> = std::collections::BTreeMap<K, V>;
+
+    #[substruct(Partial)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Full<K: Ord, V> {
+        pub pair: Pair<V>,
+        #[substruct(Partial)]
+        pub name: Name,
+        #[substruct(Partial)]
+        pub lookup: Lookup<K, V>,
+    }
+
+    let partial = Partial {
+        name: "alias".into(),
+        lookup: Lookup::from([(1u8, 3u32)]),
+    };
+    let full = partial.clone().into_full((1, 2));
+
+    assert_eq!(full.pair, (1, 2));
+    assert_eq!(full.lookup[&1], 3);
+    assert_eq!(Partial::from(full), partial);
+}
+
+#[test]
+fn test_transform_bounds() {
+    #[derive(Debug, PartialEq)]
+    struct Celsius<T>(T);
+
+    #[derive(Debug, PartialEq)]
+    struct Kelvin<T>(T);
+
+    impl<T> From<Celsius<T>> for Kelvin<T> {
+        fn from(value: Celsius<T>) -> Self {
+            Self(value.0)
+        }
+    }
+
+    impl<T> From<Kelvin<T>> for Celsius<T> {
+        fn from(value: Kelvin<T>) -> Self {
+            Self(value.0)
+        }
+    }
+
+    // Neither `T: Into<..>` nor `D: Default` are declared here. They are
+    // added to the generated impls as needed.
+    #[substruct(Reading)]
+    #[derive(Debug, PartialEq)]
+    struct Sensor<T, D> {
+        #[substruct(Reading, ty = "Celsius<T>", into)]
+        pub temperature: Kelvin<T>,
+        #[substruct(Reading, default)]
+        pub data: D,
+        #[substruct(Reading)]
+        pub previous: Option<D>,
+    }
+
+    let reading = Reading::<u8, Vec<u8>> {
+        temperature: Celsius(20),
+        previous: None,
+    };
+    let sensor = reading.into_sensor();
+
+    assert_eq!(sensor.temperature, Kelvin(20));
+    assert_eq!(sensor.data, Vec::<u8>::new());
+
+    // `NoDefault` doesn't implement `Default` but the conversion from the
+    // parent is still available.
+    struct NoDefault;
+
+    let reading = Reading::from(Sensor {
+        temperature: Kelvin(5),
+        data: NoDefault,
+        previous: None,
+    });
+    assert_eq!(reading.temperature, Celsius(5));
+}
+
+#[test]
+fn test_cfg_attr_multiple_metas() {
+    use serde::{Deserialize, Serialize};
+
+    #[substruct(Summary)]
+    #[cfg_attr(all(), derive(Clone, Debug, PartialEq), derive(Serialize, Deserialize))]
+    #[cfg_attr(all(), serde(rename_all = "camelCase"), serde(deny_unknown_fields))]
+    #[cfg_attr(any(), derive(Default))]
+    struct Report {
+        #[substruct(Summary)]
+        #[cfg_attr(all(), serde(rename = "reportId"), serde(alias = "id"))]
+        pub report_id: u32,
+        #[substruct(Summary)]
+        #[cfg_attr(all(), serde(default), serde(skip_serializing_if = "Option::is_none"))]
+        pub page_count: Option<u32>,
+        pub body: String,
+    }
+
+    let summary: Summary = serde_json::from_str(r#"{"id":4}"#).unwrap();
+    assert_eq!(
+        summary,
+        Summary {
+            report_id: 4,
+            page_count: None
+        }
+    );
+    assert_eq!(
+        serde_json::to_string(&summary).unwrap(),
+        r#"{"reportId":4}"#
+    );
+    assert!(serde_json::from_str::<Summary>(r#"{"id":4,"body":""}"#).is_err());
+
+    let report = summary.clone().into_report("body".into());
+    assert_eq!(
+        serde_json::to_string(&report).unwrap(),
+        r#"{"reportId":4,"body":"body"}"#
+    );
+}
+
+#[test]
+fn test_cow_enum() {
+    #[substruct(View, cow_enum)]
+    #[derive(Clone)]
+    struct Document<T> {
+        #[substruct(View)]
+        pub title: String,
+        #[substruct(View)]
+        pub meta: T,
+        pub body: String,
+    }
+
+    fn title_len(view: impl Into<MaybeOwnedView<'static, u32>>) -> usize {
+        view.into().title().len()
+    }
+
+    static VIEW: View<u32> = View {
+        title: String::new(),
+        meta: 0,
+    };
+
+    let view = View {
+        title: "title".to_string(),
+        meta: 5u32,
+    };
+    let borrowed = MaybeOwnedView::from(&view);
+    assert_eq!(borrowed.title(), "title");
+    assert_eq!(*borrowed.meta(), 5);
+
+    let owned = MaybeOwnedView::from(view.clone());
+    assert!(matches!(owned, MaybeOwnedView::Owned(_)));
+    assert_eq!(owned.get().title, "title");
+
+    assert_eq!(title_len(view.clone()), 5);
+    assert_eq!(title_len(&VIEW), 0);
+
+    let document = view.into_document("body".into());
+    assert_eq!(document.body, "body");
+}
+
+#[test]
+fn test_split() {
+    mod bounds {
+        pub fn from_parent(range: std::ops::Range<u32>) -> (u32, u32) {
+            (range.start, range.end)
+        }
+
+        pub fn into_parent(low: u32, high: u32) -> std::ops::Range<u32> {
+            low..high
+        }
+    }
+
+    #[substruct(Flat, summary)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Nested {
+        /// The start and end of the span.
+        #[substruct(Flat, split(start, end))]
+        pub span: (u32, u32),
+        #[substruct(Flat, split(low: u32, high: u32), with = "bounds")]
+        pub range: std::ops::Range<u32>,
+        #[substruct(Flat, split(value))]
+        pub single: (String,),
+        pub other: bool,
+    }
+
+    let nested = Nested {
+        span: (1, 2),
+        range: 3..4,
+        single: ("value".into(),),
+        other: true,
+    };
+    let flat = Flat::from(nested.clone());
+
+    assert_eq!(flat.start, 1);
+    assert_eq!(flat.end, 2);
+    assert_eq!(flat.low, 3);
+    assert_eq!(flat.high, 4);
+    assert_eq!(flat.value, "value");
+    assert_eq!(flat.into_nested(true), nested);
+}
+
+#[test]
+fn test_schemars() {
+    use schemars::{schema_for, JsonSchema};
+
+    #[substruct(CreateUser, PublicUser)]
+    #[derive(JsonSchema)]
+    #[substruct_attr(CreateUser, schemars(title = "New User"))]
+    #[allow(dead_code)]
+    struct User {
+        #[substruct(PublicUser)]
+        pub id: u64,
+        #[substruct(CreateUser, PublicUser)]
+        #[substruct_attr(CreateUser, schemars(length(min = 3)))]
+        #[substruct_attr(PublicUser, schemars(rename = "displayName"))]
+        pub name: String,
+        #[substruct(CreateUser)]
+        #[substruct_attr(CreateUser, schemars(length(min = 8)))]
+        pub password: String,
+    }
+
+    let create = serde_json::to_value(schema_for!(CreateUser)).unwrap();
+    assert_eq!(create["title"], "New User");
+    assert_eq!(create["properties"]["name"]["minLength"], 3);
+    assert_eq!(create["properties"]["password"]["minLength"], 8);
+    assert!(create["properties"].get("id").is_none());
+
+    let public = serde_json::to_value(schema_for!(PublicUser)).unwrap();
+    assert_eq!(public["title"], "PublicUser");
+    assert!(public["properties"]["displayName"]["minLength"].is_null());
+    assert!(public["properties"].get("password").is_none());
+
+    let user = serde_json::to_value(schema_for!(User)).unwrap();
+    assert_eq!(user["properties"].as_object().unwrap().len(), 3);
+}
+
+#[test]
+fn test_doc_interpolation() {
+    use schemars::{schema_for, JsonSchema};
+
+    #[substruct(
+        /// The filters used by {struct}.
+        SearchQuery,
+        ListQuery
+    )]
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Query {
+        #[substruct(
+            /// The maximum number of results returned by {struct}.
+            any(SearchQuery, ListQuery)
+        )]
+        pub limit: u32,
+    }
+
+    let search = serde_json::to_value(schema_for!(SearchQuery)).unwrap();
+    assert_eq!(search["description"], "The filters used by SearchQuery.");
+    assert_eq!(
+        search["properties"]["limit"]["description"],
+        "The maximum number of results returned by SearchQuery."
+    );
+
+    let list = serde_json::to_value(schema_for!(ListQuery)).unwrap();
+    assert_eq!(
+        list["properties"]["limit"]["description"],
+        "The maximum number of results returned by ListQuery."
+    );
+}
+
+#[test]
+fn test_map_into() {
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Id(u32);
+
+    impl From<u32> for Id {
+        fn from(value: u32) -> Self {
+            Self(value)
+        }
+    }
+
+    impl From<Id> for u32 {
+        fn from(value: Id) -> Self {
+            value.0
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Tagged<T>(T);
+
+    impl<T> From<T> for Tagged<T> {
+        fn from(value: T) -> Self {
+            Self(value)
+        }
+    }
+
+    #[substruct(Typed)]
+    #[derive(Debug, PartialEq)]
+    struct Raw<T> {
+        #[substruct(Typed, ty = "Vec<Id>", map_into)]
+        pub ids: Vec<u32>,
+        #[substruct(Typed, ty = "Vec<Tagged<T>>", map_into)]
+        pub values: Vec<T>,
+    }
+
+    let raw = Raw {
+        ids: vec![1, 2, 3],
+        values: vec!["a"],
+    };
+    let typed = Typed::from(raw);
+    assert_eq!(typed.ids, [Id(1), Id(2), Id(3)]);
+    assert_eq!(typed.values, [Tagged("a")]);
+
+    // `Tagged<T>` can't be converted back into `T` so `into_raw` is not
+    // available, but the conversion from `Raw` still is.
+    let _: fn(Raw<()>) -> Typed<()> = Typed::from;
+}
+
+#[test]
+fn test_debug_entries() {
+    #[substruct(LogFields, debug_entries)]
+    struct Request<T> {
+        #[substruct(LogFields)]
+        pub path: &'static str,
+        #[substruct(LogFields)]
+        pub r#status: T,
+        pub body: Vec<u8>,
+    }
+
+    let request = Request {
+        path: "/",
+        status: 200u16,
+        body: vec![1, 2, 3],
+    };
+    let entries: Vec<_> = request
+        .log_fields_entries()
+        .map(|(name, value)| format!("{name}={value:?}"))
+        .collect();
+
+    assert_eq!(entries, ["path=\"/\"", "status=200"]);
+
+    assert_eq!(request.body.len(), 3);
+
+    let fields = LogFields::from(request);
+    assert_eq!((fields.path, fields.status), ("/", 200));
+}
+
+#[test]
+fn test_debug_assert_on_extract() {
+    fn no_pending(order: &Order) -> bool {
+        order.pending.is_empty()
+    }
+
+    #[substruct(Receipt, debug_assert_on_extract = "no_pending")]
+    struct Order {
+        #[substruct(Receipt)]
+        pub total: u32,
+        pub pending: Vec<u32>,
+    }
+
+    let receipt = Receipt::from(Order {
+        total: 5,
+        pending: Vec::new(),
+    });
+    assert_eq!(receipt.total, 5);
+
+    let result = std::panic::catch_unwind(|| {
+        Receipt::from(Order {
+            total: 5,
+            pending: vec![1],
+        })
+    });
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+}
+
+#[test]
+fn test_try_into_lossless() {
+    use std::convert::Infallible;
+
+    fn convert<T, U>(value: T) -> U
+    where
+        T: TryInto<U, Error = Infallible>,
+    {
+        match value.try_into() {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    #[substruct(Same)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Original {
+        #[substruct(Same)]
+        pub a: u32,
+        #[substruct(Same)]
+        pub b: String,
+    }
+
+    let original = Original {
+        a: 1,
+        b: "b".into(),
+    };
+
+    // These come from the standard library's blanket impls on top of the
+    // generated `From` impls.
+    let same: Same = convert(original.clone());
+    let back: Original = convert(same);
+    assert_eq!(back, original);
+}
+
+#[test]
+fn test_ref_conversion() {
+    #[substruct(SubQuery, ref)]
+    #[derive(Debug, PartialEq)]
+    struct Query {
+        #[substruct(SubQuery)]
+        pub filter: String,
+        #[substruct(SubQuery)]
+        pub limit: u32,
+        pub page: usize,
+    }
+
+    // `SubQuery` itself doesn't need to implement `Clone`.
+    let sub = SubQuery {
+        filter: "name".into(),
+        limit: 10,
+    };
+    let first = sub.to_query(1);
+    let second = sub.to_query(2);
+
+    assert_eq!(first.filter, second.filter);
+    assert_eq!((first.limit, first.page), (10, 1));
+    assert_eq!(sub.into_query(3).page, 3);
+}
+
+#[test]
+fn test_empty_child() {
+    #[substruct(Marker)]
+    #[derive(Debug, PartialEq)]
+    struct Message {
+        pub id: u32,
+        pub body: String,
+    }
+
+    #[substruct(TupleMarker)]
+    #[derive(Debug, PartialEq)]
+    struct Pair(pub u32, pub bool);
+
+    let message = Marker {}.into_message(1, "body".into());
+    assert_eq!(
+        message,
+        Message {
+            id: 1,
+            body: "body".into()
+        }
+    );
+    assert_eq!(Marker::from(message), Marker {});
+
+    let pair = TupleMarker().into_pair(1, true);
+    assert_eq!(pair, Pair(1, true));
+    assert_eq!(TupleMarker::from(pair), TupleMarker());
+}
+
+#[test]
+fn test_method_attrs() {
+    #[substruct(
+        Fast,
+        inline,
+        ref,
+        Checked,
+        method_attr(must_use),
+        method_attr(inline(always))
+    )]
+    #[derive(Debug, PartialEq)]
+    struct Vector {
+        #[substruct(Fast, Checked)]
+        pub x: f32,
+        #[substruct(Fast, Checked)]
+        pub y: f32,
+        pub z: f32,
+    }
+
+    let fast = Fast { x: 1.0, y: 2.0 };
+    assert_eq!(fast.to_vector(3.0), fast.into_vector(3.0));
+
+    let vector = Checked { x: 1.0, y: 2.0 }.into_vector(3.0);
+    assert_eq!(
+        vector,
+        Vector {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0
+        }
+    );
+}
+
+#[test]
+fn test_const() {
+    #[substruct(Base, const)]
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        #[substruct(Base)]
+        pub name: &'static str,
+        #[substruct(Base)]
+        pub retries: u32,
+        pub verbose: bool,
+    }
+
+    #[substruct(Small, const)]
+    #[derive(Debug, PartialEq)]
+    enum Level {
+        #[substruct(Small)]
+        Low(u8),
+        High,
+    }
+
+    const CONFIG: Config = Base {
+        name: "name",
+        retries: 3,
+    }
+    .into_config(true);
+    const LEVEL: Level = Small::Low(1).into_level();
+
+    assert_eq!(
+        CONFIG,
+        Config {
+            name: "name",
+            retries: 3,
+            verbose: true
+        }
+    );
+    assert_ne!(LEVEL, Level::High);
+    assert_eq!(LEVEL, Level::Low(1));
+}
+
+#[test]
+fn test_cfg_fields() {
+    #[substruct(Child, getters, Split, Borrowed, borrow)]
+    #[derive(Debug, PartialEq)]
+    struct Parent {
+        #[substruct(Child, Borrowed)]
+        pub a: u32,
+        #[substruct(Child, Borrowed)]
+        #[cfg(any())]
+        pub b: u32,
+        #[cfg(any())]
+        pub c: String,
+        #[substruct(Split, split(d1: u8, d2: u8))]
+        #[cfg(any())]
+        pub d: (u8, u8),
+        #[cfg(not(any()))]
+        pub e: bool,
+    }
+
+    let parent = Child { a: 1 }.into_parent(true);
+    assert_eq!(parent, Parent { a: 1, e: true });
+    assert_eq!(*Borrowed::from(&parent).a, 1);
+    assert_eq!(*Child::from(parent).a(), 1);
+    assert_eq!(Split {}.into_parent(1, false), Parent { a: 1, e: false });
+}
+
+#[test]
+fn test_cfg_fields_merge() {
+    #[substruct(Head, merges(Tail), Tail)]
+    #[derive(Debug, PartialEq)]
+    struct Parent {
+        #[substruct(Head)]
+        pub a: u32,
+        #[substruct(Tail)]
+        pub b: u32,
+        #[cfg(any())]
+        pub c: String,
+        #[substruct(Tail)]
+        #[cfg(any())]
+        pub d: String,
+        pub e: bool,
+    }
+
+    let parent = Head { a: 1 }.merge(Tail { b: 2 }, true);
+    assert_eq!(
+        parent,
+        Parent {
+            a: 1,
+            b: 2,
+            e: true
+        }
+    );
+}
+
+#[test]
+fn test_roundtrip() {
+    mod lossy {
+        pub fn into_parent(value: i64) -> i64 {
+            value
+        }
+
+        pub fn from_parent(value: i64) -> i64 {
+            value.clamp(0, 10)
+        }
+    }
+
+    #[substruct(Exact, roundtrip, Clamped, roundtrip)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Parent {
+        #[substruct(Exact)]
+        pub name: String,
+        #[substruct(Clamped, with = "lossy")]
+        pub value: i64,
+    }
+
+    let exact = Exact {
+        name: "name".into(),
+    };
+    assert!(exact.roundtrip(5));
+
+    assert!(Clamped { value: 5 }.roundtrip("name".into()));
+    assert!(!Clamped { value: 20 }.roundtrip("name".into()));
+}
+
+#[test]
+fn test_derive() {
+    #[substruct(
+        WireFormat,
+        derive(serde::Serialize, serde::Deserialize),
+        derive(Clone)
+    )]
+    #[substruct_attr(WireFormat, serde(rename_all = "camelCase"))]
+    #[derive(Debug, PartialEq)]
+    struct Message {
+        #[substruct(WireFormat)]
+        pub message_id: u32,
+        #[substruct(WireFormat)]
+        #[substruct_attr(WireFormat, serde(rename = "text"))]
+        pub body: String,
+        pub received: bool,
+    }
+
+    let wire = WireFormat {
+        message_id: 1,
+        body: "body".into(),
+    };
+    let json = serde_json::to_string(&wire.clone()).unwrap();
+    assert_eq!(json, r#"{"messageId":1,"text":"body"}"#);
+    assert_eq!(serde_json::from_str::<WireFormat>(&json).unwrap(), wire);
+}
+
+#[test]
+fn test_raw_identifiers() {
+    #[substruct(r#Child, ref, debug_entries, field_names, r#Chained, chain_to = r#Leaf)]
+    #[substruct_attr(r#Chained, substruct(r#Leaf))]
+    #[derive(Clone, Debug, PartialEq)]
+    struct r#Parent {
+        #[substruct(r#Child, Chained)]
+        #[substruct_attr(r#Chained, substruct(Leaf))]
+        pub r#type: String,
+        #[substruct(r#Chained)]
+        pub r#match: bool,
+    }
+
+    let child = r#Child {
+        r#type: "type".into(),
+    };
+    let parent = child.to_parent(true);
+    assert_eq!(parent, child.into_parent(true));
+    assert_eq!(Child::FIELD_NAMES, ["type"]);
+    assert_eq!(parent.child_entries().next().unwrap().0, "type");
+    assert_eq!(parent.into_leaf().r#type, "type");
+}
+
+#[test]
+fn test_extra() {
+    #[substruct(Extended, extra(timestamp: u64, source: String))]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Event {
+        #[substruct(Extended)]
+        pub id: u32,
+        #[substruct(Extended)]
+        pub kind: String,
+    }
+
+    let event = Event {
+        id: 1,
+        kind: "kind".into(),
+    };
+    let extended = event.clone().into_extended(5, "source".into());
+    assert_eq!(extended.timestamp, 5);
+    assert_eq!(extended.source, "source");
+    assert_eq!(extended.id, 1);
+    assert_eq!(Event::from(extended), event);
+}
+
+#[test]
+fn test_arg_modes() {
+    #[substruct(Header, into_args, fill_default, Summary, ref_args, ref)]
+    #[derive(Debug, PartialEq)]
+    struct Document {
+        #[substruct(Header, Summary)]
+        pub title: String,
+        pub body: String,
+        #[substruct(Header)]
+        pub tags: Vec<String>,
+    }
+
+    let header = Header {
+        title: "title".into(),
+        tags: vec!["tag".into()],
+    };
+    let document = header.into_document("body");
+    assert_eq!(document.body, "body");
+    assert_eq!(Document::from(Header::from(document)).body, "");
+
+    let body = String::from("body");
+    let tags = vec!["tag".to_owned()];
+    let summary = Summary {
+        title: "title".into(),
+    };
+    assert_eq!(
+        summary.to_document(&body, &tags),
+        summary.into_document(&body, &tags)
+    );
+    assert_eq!(tags.len(), 1);
+}
+
+#[test]
+fn test_nested_parent() {
+    #[substruct(Medium, Small, parent = Medium, Tiny, parent = Small)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Full {
+        #[substruct(Medium, Small, Tiny)]
+        pub id: u32,
+        #[substruct(Medium, Small)]
+        #[substruct_rename(Small, label)]
+        pub name: String,
+        #[substruct(Medium)]
+        pub value: u64,
+        pub extra: bool,
+    }
+
+    let medium = Medium {
+        id: 1,
+        name: "name".into(),
+        value: 5,
+    };
+    let small = Small::from(medium.clone());
+    assert_eq!(
+        small,
+        Small {
+            id: 1,
+            label: "name".into()
+        }
+    );
+    assert_eq!(small.clone().into_medium(5), medium);
+
+    let tiny = Tiny::from(small.clone());
+    assert_eq!(tiny, Tiny { id: 1 });
+    assert_eq!(tiny.into_small("name".into()), small);
+
+    assert!(medium.into_full(true).extra);
+}
+
+#[test]
+fn test_from_ref() {
+    #[substruct(Owned, from_ref)]
+    #[derive(Debug, PartialEq)]
+    struct Entry<T> {
+        #[substruct(Owned)]
+        pub key: String,
+        #[substruct(Owned)]
+        pub value: T,
+    }
+
+    #[substruct(Partial, from_ref, rest_default)]
+    #[derive(Debug, Default, PartialEq)]
+    struct Settings {
+        #[substruct(Partial)]
+        pub name: String,
+        pub retries: u32,
+    }
+
+    let owned = Owned {
+        key: "key".into(),
+        value: vec![1, 2],
+    };
+    let entry = Entry::from(&owned);
+    assert_eq!(entry, Entry::from(owned));
+
+    let partial = Partial {
+        name: "name".into(),
+    };
+    let settings = Settings::from(&partial);
+    assert_eq!(settings.name, partial.name);
+    assert_eq!(settings.retries, 0);
+}
+
+#[test]
+fn test_enum_variants() {
+    #[substruct(InputEvent, KeyEvent)]
+    #[derive(Clone, Debug, PartialEq)]
+    enum Event {
+        #[substruct(InputEvent, KeyEvent)]
+        Key(char),
+        #[substruct(InputEvent)]
+        Click {
+            x: i32,
+            y: i32,
+        },
+        #[substruct(InputEvent)]
+        Focus,
+        Custom(String),
+    }
+
+    let click = Event::Click { x: 1, y: 2 };
+    assert_eq!(
+        InputEvent::try_from(click.clone()),
+        Ok(InputEvent::Click { x: 1, y: 2 })
+    );
+    assert_eq!(KeyEvent::try_from(click.clone()), Err(click));
+    assert_eq!(
+        InputEvent::try_from(Event::Custom("custom".into())),
+        Err(Event::Custom("custom".into()))
+    );
+
+    let event: Event = KeyEvent::Key('a').into();
+    assert_eq!(event, Event::Key('a'));
+    assert_eq!(InputEvent::Focus.into_event(), Event::Focus);
+}
+
+#[test]
+fn test_enum_all_variants() {
+    #[substruct(Copy)]
+    #[derive(Debug, PartialEq)]
+    enum Original {
+        #[substruct(Copy)]
+        A,
+        #[substruct(Copy)]
+        B(u32),
+    }
+
+    assert_eq!(Copy::from(Original::B(1)), Copy::B(1));
+    assert_eq!(Original::from(Copy::A), Original::A);
+}
+
+#[test]
+fn test_derive_default() {
+    #[derive(Debug, PartialEq)]
+    struct Handle;
+
+    #[substruct(MiniConfig, default, Mirror, default)]
+    #[derive(Debug, PartialEq)]
+    #[substruct_attr(Mirror, derive(Default))]
+    struct Config {
+        #[substruct(MiniConfig, Mirror)]
+        pub name: String,
+        #[substruct(MiniConfig)]
+        pub retries: u32,
+        pub handle: Handle,
+    }
+
+    let config = MiniConfig::default();
+    assert_eq!(config.name, "");
+    assert_eq!(config.retries, 0);
+
+    let config = config.into_config(Handle);
+    assert_eq!(config.name, "");
+    assert_eq!(
+        Mirror::default(),
+        Mirror {
+            name: String::new()
+        }
+    );
+}
+
+#[test]
+fn test_fill_default() {
+    #[substruct(Partial, fill_default)]
+    #[derive(Debug, PartialEq)]
+    struct Config<T> {
+        #[substruct(Partial)]
+        pub name: String,
+        #[substruct(Partial)]
+        pub tag: Option<T>,
+        pub retries: u32,
+        pub extra: Vec<T>,
+    }
+
+    let config: Config<u8> = Partial {
+        name: "test".into(),
+        tag: Some(1),
+    }
+    .into();
+    assert_eq!(
+        config,
+        Config {
+            name: "test".into(),
+            tag: Some(1),
+            retries: 0,
+            extra: Vec::new(),
+        }
+    );
+}
+
+#[test]
+fn test_rest_default() {
+    #[substruct(Partial, rest_default)]
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        #[substruct(Partial)]
+        pub name: String,
+        pub retries: u32,
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Self {
+                name: "default".into(),
+                retries: 3,
+            }
+        }
+    }
+
+    let partial = Partial {
+        name: "test".into(),
+    };
+    assert_eq!(
+        partial.into_config(),
+        Config {
+            name: "test".into(),
+            retries: 3
+        }
+    );
+    assert_eq!(
+        Config::from(Partial { name: "a".into() }),
+        Config {
+            name: "a".into(),
+            retries: 3
+        }
+    );
+}
+
+#[test]
+fn test_optional() {
+    #[substruct(UserPatch, optional)]
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct User {
+        #[substruct(UserPatch)]
+        pub name: String,
+        #[substruct(UserPatch)]
+        pub age: u32,
+        pub id: u64,
+    }
+
+    let patch = UserPatch {
+        name: Some("new".into()),
+        age: None,
+    };
+    assert_eq!(patch.clone(), patch);
+    assert_eq!(UserPatch::default().name, None);
+
+    let mut user = User {
+        name: "old".into(),
+        age: 30,
+        id: 7,
+    };
+    patch.apply_to(&mut user);
+    assert_eq!(
+        user,
+        User {
+            name: "new".into(),
+            age: 30,
+            id: 7,
+        }
+    );
+}
+
+#[test]
+fn test_optional_serde() {
+    use serde::{Deserialize, Serialize};
+
+    #[substruct(SettingsPatch, optional)]
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Settings {
+        #[substruct(SettingsPatch)]
+        pub theme: String,
+        #[substruct(SettingsPatch)]
+        pub font_size: u32,
+    }
+
+    let patch = SettingsPatch {
+        theme: Some("dark".into()),
+        font_size: None,
+    };
+    assert_eq!(
+        serde_json::to_string(&patch).unwrap(),
+        r#"{"theme":"dark"}"#
+    );
+
+    let patch: SettingsPatch = serde_json::from_str(r#"{"font_size":12}"#).unwrap();
+    assert_eq!(patch.theme, None);
+
+    let mut settings = Settings {
+        theme: "light".into(),
+        font_size: 10,
+    };
+    patch.apply_to(&mut settings);
+    assert_eq!(settings.font_size, 12);
+}
+
+#[test]
+fn test_merges() {
+    #[substruct(ConfigA, merges(ConfigB), ConfigB)]
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        #[substruct(ConfigA)]
+        pub a: u32,
+        #[substruct(ConfigB)]
+        #[substruct_rename(ConfigB, renamed)]
+        pub b: String,
+        #[substruct(ConfigA)]
+        pub c: bool,
+        pub d: u8,
+    }
+
+    let a = ConfigA { a: 1, c: true };
+    let b = ConfigB {
+        renamed: "b".into(),
+    };
+    assert_eq!(
+        a.merge(b, 4),
+        Config {
+            a: 1,
+            b: "b".into(),
+            c: true,
+            d: 4
+        }
+    );
+}
+
+#[test]
+fn test_merges_arg_options() {
+    #[substruct(ByRef, merges(Tail), ref_args, ByInto, merges(Tail), into_args, Tail)]
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        #[substruct(ByRef, ByInto)]
+        pub a: u32,
+        #[substruct(Tail)]
+        pub b: String,
+        pub c: String,
+    }
+
+    let expected = Config {
+        a: 1,
+        b: "b".into(),
+        c: "c".into(),
+    };
+
+    let tail = || Tail { b: "b".into() };
+    assert_eq!(ByRef { a: 1 }.merge(tail(), &"c".to_owned()), expected);
+    assert_eq!(ByInto { a: 1 }.merge(tail(), "c"), expected);
+}
+
+#[test]
+fn test_struct_attr() {
+    #[substruct(Strict, attr(serde(deny_unknown_fields)), attr(derive(Default)))]
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Request {
+        #[substruct(Strict)]
+        pub id: u32,
+        pub body: String,
+    }
+
+    let json = r#"{ "id": 1, "body": "text", "extra": true }"#;
+    assert!(serde_json::from_str::<Request>(json).is_ok());
+    assert!(serde_json::from_str::<Strict>(r#"{ "id": 1, "extra": true }"#).is_err());
+    assert_eq!(Strict::default(), Strict { id: 0 });
+}
+
+#[test]
+fn test_field_names() {
+    #[substruct(Query, field_names, Parent, field_names)]
+    #[allow(dead_code)]
+    struct Parent {
+        #[substruct(Query)]
+        pub id: u32,
+        #[substruct(Query)]
+        #[substruct_rename(Query, r#type)]
+        pub kind: String,
+        pub body: String,
+    }
+
+    #[substruct(Second, field_names)]
+    #[allow(dead_code)]
+    struct Pair(pub u32, #[substruct(Second)] pub u64);
+
+    assert_eq!(Query::FIELD_NAMES, ["id", "type"]);
+    assert_eq!(Parent::FIELD_NAMES, ["id", "kind", "body"]);
+    assert_eq!(Second::FIELD_NAMES, ["0"]);
+}
+
+#[test]
+fn test_no_reverse_from() {
+    #[substruct(Mirror, no_reverse_from)]
+    #[derive(Debug, PartialEq)]
+    struct Point {
+        #[substruct(Mirror)]
+        pub x: i32,
+        #[substruct(Mirror)]
+        pub y: i32,
+    }
+
+    impl From<Mirror> for Point {
+        fn from(value: Mirror) -> Self {
+            Self {
+                x: -value.x,
+                y: -value.y,
+            }
+        }
+    }
+
+    assert_eq!(Point::from(Mirror { x: 1, y: 2 }), Point { x: -1, y: -2 });
+    assert_eq!(Mirror { x: 1, y: 2 }.into_point(), Point { x: 1, y: 2 });
+}
+
+#[test]
+fn test_skip_conversions() {
+    #[substruct(Celsius, skip_conversions)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Reading {
+        #[substruct(Celsius)]
+        pub temperature: f64,
+        pub fahrenheit: bool,
+    }
+
+    impl From<Reading> for Celsius {
+        fn from(value: Reading) -> Self {
+            let temperature = match value.fahrenheit {
+                true => (value.temperature - 32.0) * 5.0 / 9.0,
+                false => value.temperature,
+            };
+
+            Self { temperature }
+        }
+    }
+
+    impl Celsius {
+        fn into_reading(self) -> Reading {
+            Reading {
+                temperature: self.temperature,
+                fahrenheit: false,
+            }
+        }
+    }
+
+    let reading = Reading {
+        temperature: 212.0,
+        fahrenheit: true,
+    };
+    let celsius = Celsius::from(reading);
+
+    assert_eq!(celsius, Celsius { temperature: 100.0 });
+    assert_eq!(
+        celsius.into_reading(),
+        Reading {
+            temperature: 100.0,
+            fahrenheit: false
+        }
+    );
+}
+
+#[test]
+fn test_via_try_into() {
+    #[substruct(Compact)]
+    #[derive(Debug, PartialEq)]
+    struct Stats {
+        #[substruct(Compact, ty = "u8", via = "try_into")]
+        pub count: u64,
+        #[substruct(Compact, ty = "i16", via = "try_into")]
+        pub delta: i64,
+        #[substruct(Compact)]
+        pub name: String,
+    }
+
+    let stats = Stats {
+        count: 3,
+        delta: -4,
+        name: "stats".into(),
+    };
+    let compact = Compact::try_from(stats).unwrap();
+    assert_eq!(compact.count, 3u8);
+    assert_eq!(compact.delta, -4i16);
+    assert_eq!(compact.into_stats().count, 3u64);
+
+    let result = Compact::try_from(Stats {
+        count: 300,
+        delta: 0,
+        name: "stats".into(),
+    });
+    assert!(matches!(result, Err(CompactConversionError::Count(_))));
+
+    let result = Compact::try_from(Stats {
+        count: 0,
+        delta: i64::MAX,
+        name: "stats".into(),
+    });
+    assert!(matches!(result, Err(CompactConversionError::Delta(_))));
+}
+
+#[test]
+fn test_getters() {
+    mod inner {
+        use substruct::substruct;
+
+        #[substruct(Dto, getters, vis = "pub(crate)")]
+        pub struct Record {
+            #[substruct(Dto)]
+            id: u32,
+            #[substruct(Dto)]
+            name: String,
+            #[allow(dead_code)]
+            extra: Vec<u8>,
+        }
+
+        pub fn dto() -> Dto {
+            Dto {
+                id: 1,
+                name: "name".into(),
+            }
+        }
+    }
+
+    let dto = inner::dto();
+    assert_eq!(*dto.id(), 1);
+    assert_eq!(dto.name(), "name");
+}
+
+#[test]
+fn test_iter() {
+    #[substruct(Vec3, iter, Vec2, iter)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Vec4<T> {
+        #[substruct(Vec2, Vec3)]
+        pub x: T,
+        #[substruct(Vec2, Vec3)]
+        pub y: T,
+        #[substruct(Vec3)]
+        pub z: T,
+        pub w: T,
+    }
+
+    #[substruct(Pair, iter)]
+    #[allow(dead_code)]
+    struct Triple(#[substruct(Pair)] String, #[substruct(Pair)] String, u32);
+
+    let v = Vec3 { x: 1, y: 2, z: 3 };
+    assert_eq!(v.iter().sum::<i32>(), 6);
+    assert_eq!(v.into_iter().collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(Vec2 { x: 4, y: 5 }.iter().max(), Some(&5));
+
+    let pair = Pair("a".into(), "b".into());
+    assert_eq!(pair.iter().map(String::as_str).collect::<String>(), "ab");
+    assert_eq!(pair.into_iter().collect::<Vec<_>>(), ["a", "b"]);
+}
+
+#[test]
+fn test_rename() {
+    #[substruct(Message, Envelope)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct HttpRequest {
+        #[substruct(Message, Envelope)]
+        #[substruct_rename(Message, payload)]
+        #[substruct_rename(any(Message, Envelope), contents)]
+        pub body: Vec<u8>,
+        #[substruct(Envelope)]
+        #[substruct_rename(Envelope, r#type)]
+        pub kind: &'static str,
+        pub path: String,
+    }
+
+    let message = Message { payload: vec![1] };
+    let request = message.clone().into_http_request("text", "/".into());
+    assert_eq!(request.body, [1]);
+    assert_eq!(Message::from(request.clone()), message);
+
+    let envelope = Envelope::from(HttpRequest {
+        kind: "json",
+        ..request
+    });
+    assert_eq!(envelope.contents, [1]);
+    assert_eq!(envelope.r#type, "json");
+}
+
+#[test]
+fn test_substruct_group() {
+    #[substruct(Parent, A, B, C, D)]
+    #[substruct_group(Small = (A, B), Smaller = (Small, C))]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Parent {
+        #[substruct(Smaller, D)]
+        #[substruct_attr(Small, allow(dead_code))]
+        pub a: u32,
+        #[substruct(Small)]
+        #[substruct_rename(Small, renamed)]
+        pub b: u32,
+        #[substruct(not(Small))]
+        pub c: u32,
+    }
+
+    let a = A { a: 1, renamed: 2 };
+    let b = B::from(a.clone().into_parent(3));
+    assert_eq!(b, B { a: 1, renamed: 2 });
+
+    let c = C { a: 1, c: 3 };
+    let d = D::from(c.clone().into_parent(2));
+    assert_eq!(d.a, c.a);
+    assert_eq!(d.c, c.c);
+}
+
+#[test]
+fn test_borrow() {
+    #[substruct(RecordView, borrow)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Record<T> {
+        #[substruct(RecordView)]
+        pub name: String,
+        #[substruct(RecordView)]
+        #[substruct_rename(RecordView, bytes)]
+        pub data: Vec<T>,
+        pub id: u64,
+    }
+
+    #[substruct(PairView, borrow)]
+    struct Pair<'a>(#[substruct(PairView)] &'a str, u32);
+
+    let record = Record {
+        name: "record".to_string(),
+        data: vec![1u8, 2, 3],
+        id: 5,
+    };
+    let view = RecordView::from(&record);
+
+    assert_eq!(view.name, "record");
+    assert_eq!(view.bytes, &[1, 2, 3]);
+    assert_eq!(view, view.clone());
+    assert!(std::ptr::eq(view.name, &record.name));
+    assert_eq!(record.id, 5);
+
+    let pair = Pair("a", 1);
+    let view = PairView::from(&pair);
+    assert_eq!((*view.0, pair.1), ("a", 1));
+}
+
+#[test]
+fn test_exclusive() {
+    #[substruct(A, B, C)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Parent {
+        #[substruct(A, B, C)]
+        a: u32,
+        #[substruct(exclusive, B)]
+        b: String,
+        #[substruct(exclusive, not(any(Parent, A, B)))]
+        c: Option<u8>,
+    }
+
+    #[substruct(Full, profiles(Profile(Layer)))]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Settings {
+        #[substruct(Full)]
+        a: u32,
+        #[substruct(exclusive, Full)]
+        b: u32,
+    }
+
+    let parent = Parent { a: 1 };
+    let b = B::from(parent.clone());
+    assert_eq!(
+        b,
+        B {
+            a: 1,
+            b: String::new()
+        }
+    );
+    assert_eq!(b.into_parent(), parent);
+
+    let c = C { a: 2, c: Some(3) };
+    assert_eq!(c.into_parent(), Parent { a: 2 });
+    assert_eq!(A::from(parent), A { a: 1 });
+
+    assert_eq!(Profile::ALL, &[Profile::Layer]);
+    let settings = Profile::merge_all(Settings { a: 1 }, Layer { a: Some(2) });
+    assert_eq!(settings, Settings { a: 2 });
+}
+
+#[test]
+fn test_profiles_tuple_exclusive() {
+    #[substruct(Full, skip_conversions, profiles(Profile(Layer)))]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Pair(
+        #[substruct(exclusive, Full)] u32,
+        #[substruct(Full)] u32,
+        #[substruct(Full)] String,
+    );
+
+    assert_eq!(Profile::ALL, &[Profile::Layer]);
+    let full = Full(0, 1, "a".into());
+    let pair = Profile::merge_all(Pair(full.1, full.2), Layer(None, Some("b".into())));
+    assert_eq!(pair, Pair(1, "b".into()));
+}
+
+#[test]
+fn test_assert_subset() {
+    #[substruct(
+        Summary,
+        trim_generics,
+        assert_subset,
+        Patch,
+        optional,
+        assert_subset,
+        View,
+        borrow,
+        assert_subset
+    )]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Item<T> {
+        #[substruct(Summary, Patch, View)]
+        name: String,
+        #[substruct(Summary, into, ty = "Box<str>")]
+        label: String,
+        #[substruct(Patch, View)]
+        value: T,
+    }
+
+    let item = Item {
+        name: "a".to_string(),
+        label: "b".to_string(),
+        value: 2u8,
+    };
+    assert_eq!(View::from(&item).value, &2);
+    assert_eq!(&*Summary::from(item.clone()).label, "b");
+}
+
+#[test]
+fn test_named_tuple_child() {
+    #[substruct(Rgb, named(r, g, b), Alpha, named(a))]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Rgba(
+        #[substruct(Rgb)] u8,
+        #[substruct(Rgb)] u8,
+        #[substruct(Rgb)] u8,
+        #[substruct(Alpha)] u8,
+    );
+
+    let color = Rgba(1, 2, 3, 4);
+    let rgb = Rgb::from(color);
+    assert_eq!(rgb, Rgb { r: 1, g: 2, b: 3 });
+    assert_eq!(rgb.into_rgba(4), color);
+    assert_eq!(Alpha::from(color).a, 4);
+}
+
+#[test]
+fn test_meta() {
+    #[substruct(Small, Large, extra(note: String), meta)]
+    #[allow(dead_code)]
+    struct Full {
+        #[substruct(Small, Large)]
+        a: u32,
+        #[substruct(Large)]
+        b: u32,
+        c: u32,
+    }
+
+    #[substruct(Tiny, meta = point_meta)]
+    #[allow(dead_code)]
+    struct Point(#[substruct(Tiny)] i32, i32);
+
+    assert_eq!(substruct_meta::CHILD_COUNT, 2);
+    assert_eq!(substruct_meta::FULL_FIELDS, 3);
+    assert_eq!(substruct_meta::SMALL_FIELDS, 1);
+    assert_eq!(substruct_meta::LARGE_FIELDS, 3);
+
+    assert_eq!(point_meta::CHILD_COUNT, 1);
+    assert_eq!(point_meta::POINT_FIELDS, 2);
+    assert_eq!(point_meta::TINY_FIELDS, 1);
+}
+
+#[test]
+fn test_sibling_from() {
+    #[substruct(Request, sibling_from, Response, sibling_from, Summary)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Message {
+        #[substruct(Request, Response, Summary)]
+        #[substruct_rename(Response, message_id)]
+        id: u64,
+        #[substruct(Request, Response)]
+        body: String,
+        sent: bool,
+    }
+
+    let request = Request {
+        id: 1,
+        body: "hello".to_string(),
+    };
+    let response = Response::from(request.clone());
+    assert_eq!(response.message_id, 1);
+    assert_eq!(response.body, "hello");
+    assert_eq!(Request::from(response), request);
+    assert_eq!(Summary::from(request.into_message(true)).id, 1);
+}
+
+#[test]
+fn test_core() {
+    #[substruct(Create, core = Common, Update, core = Common, Lookup)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct User {
+        #[substruct(Create, Update, Lookup)]
+        name: String,
+        #[substruct(Create, Update)]
+        email: String,
+        #[substruct(Create)]
+        password: String,
+        #[substruct(Update, Lookup)]
+        id: u64,
+    }
+
+    let user = User {
+        name: "user".to_string(),
+        email: "user@example.com".to_string(),
+        password: "hunter2".to_string(),
+        id: 5,
+    };
+
+    let mut update = Update::from(user.clone());
+    assert_eq!(
+        update.common,
+        Common {
+            name: "user".to_string(),
+            email: "user@example.com".to_string(),
+        }
+    );
+    assert_eq!(update.id, 5);
+
+    // Fields of the core are accessible through `Deref` and `DerefMut`.
+    update.name = "renamed".to_string();
+    assert_eq!(update.email, "user@example.com");
+
+    let create = Create {
+        common: update.common.clone(),
+        password: "hunter2".to_string(),
+    };
+    let updated = create.into_user(5);
+    assert_eq!(updated.name, "renamed");
+    assert_eq!(update.into_user("hunter2".to_string()), updated);
+    assert_eq!(Lookup::from(user).name, "user");
+}
+
+#[test]
+fn test_core_generics() {
+    #[substruct(Create, core = Common, Update, core = Common)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Entry<T> {
+        #[substruct(Create, Update)]
+        id: u32,
+        #[substruct(Create)]
+        value: T,
+        #[substruct(Update)]
+        history: Vec<T>,
+    }
+
+    let entry = Entry {
+        id: 1,
+        value: 5u8,
+        history: vec![1, 2],
+    };
+
+    let create = Create::from(entry.clone());
+    assert_eq!(create.common, Common { id: 1 });
+    assert_eq!(create.id, 1);
+    assert_eq!(create.into_entry(vec![1, 2]), entry);
+
+    let update = Update::from(entry.clone());
+    assert_eq!(update.into_entry(5), entry);
+}
+
+#[test]
+fn test_from_parent() {
+    #[substruct(Named, Full)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Parent<T> {
+        #[substruct(Named, Full)]
+        #[substruct_rename(Named, renamed)]
+        a: T,
+        #[substruct(Full)]
+        b: String,
+    }
+
+    #[substruct(Pair, Everything)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Tuple(
+        #[substruct(Everything)] u8,
+        #[substruct(Pair, Everything)] u16,
+        #[substruct(Pair, Everything)] u32,
+    );
+
+    fn convert<P, C: From<P>>(parent: P) -> C {
+        parent.into()
+    }
+
+    let parent = Parent {
+        a: 1u8,
+        b: "b".to_string(),
+    };
+    let named: Named<u8> = convert(parent.clone());
+    assert_eq!(named.renamed, 1);
+    let full: Full<u8> = convert(parent.clone());
+    assert_eq!(full.b, "b");
+    assert_eq!(convert::<_, Parent<u8>>(full), parent);
+
+    let tuple = Tuple(1, 2, 3);
+    let pair: Pair = convert(tuple.clone());
+    assert_eq!((pair.0, pair.1), (2, 3));
+    let everything: Everything = convert(tuple.clone());
+    assert_eq!(Tuple::from(everything), tuple);
+}
+
+#[test]
+fn test_attr_interpolation() {
+    #[substruct(Alpha, Beta)]
+    #[derive(serde::Serialize)]
+    struct Versioned {
+        #[substruct(Alpha, Beta)]
+        #[substruct_attr(any(Alpha, Beta), serde(rename = "{struct}_v2"))]
+        id: u32,
+    }
+
+    let alpha = serde_json::to_value(Alpha { id: 1 }).unwrap();
+    let beta = serde_json::to_value(Beta { id: 2 }).unwrap();
+    let parent = serde_json::to_value(Versioned { id: 3 }).unwrap();
+
+    assert_eq!(alpha, serde_json::json!({ "Alpha_v2": 1 }));
+    assert_eq!(beta, serde_json::json!({ "Beta_v2": 2 }));
+    assert_eq!(parent, serde_json::json!({ "id": 3 }));
+}
+
+#[test]
+fn test_narrow() {
+    #[substruct(Compact, narrow(count: u32, offset: i8))]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Stats {
+        #[substruct(Compact)]
+        count: u64,
+        #[substruct(Compact)]
+        offset: i64,
+        name: String,
+    }
+
+    let stats = Stats {
+        count: 5,
+        offset: -3,
+        name: "stats".to_string(),
+    };
+    let compact = Compact::try_from(stats.clone()).unwrap();
+    let _: u32 = compact.count;
+    assert_eq!((compact.count, compact.offset), (5, -3));
+    assert_eq!(compact.into_stats("stats".to_string()), stats);
+
+    let error = Compact::try_from(Stats {
+        count: u64::MAX,
+        ..stats
+    })
+    .unwrap_err();
+    assert!(matches!(error, CompactConversionError::Count(_)));
+}
+
+#[test]
+fn test_all_fields_child() {
+    #[substruct(Record, Mirror, Copy1, Copy2)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Record {
+        #[substruct(Mirror, Copy1, Copy2)]
+        a: u32,
+        #[substruct(Mirror, Copy1, Copy2)]
+        b: String,
+    }
+
+    let record = Record {
+        a: 1,
+        b: "b".to_string(),
+    };
+
+    // Each of the generated structs with every field converts back into the
+    // parent with `From`.
+    let copy1 = Copy1::from(record.clone());
+    let copy2 = Copy2::from(record.clone());
+    assert_eq!(Record::from(copy1), record);
+    assert_eq!(Record::from(copy2), record);
+    assert_eq!(Mirror::from(record.clone()).into_record(), record);
+}
+
+#[test]
+fn test_repr() {
+    #[substruct(Aligned, repr = "C, align(16)", Packed, repr = "C, packed")]
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct Layout {
+        #[substruct(Aligned, Packed)]
+        a: u8,
+        #[substruct(Aligned, Packed)]
+        b: u32,
+    }
+
+    #[substruct(Small, repr = "C")]
+    #[allow(dead_code)]
+    union Bits {
+        #[substruct(Small)]
+        byte: u8,
+        word: u64,
+    }
+
+    assert_eq!(std::mem::size_of::<Layout>(), 8);
+    assert_eq!(std::mem::align_of::<Aligned>(), 16);
+    assert_eq!(std::mem::size_of::<Packed>(), 5);
+    assert_eq!(std::mem::size_of::<Small>(), 1);
+}
+
+#[test]
+fn test_max_size() {
+    #[substruct(Small, trim_generics, max_size = 8, Borrowed, max_size = 2 * 8)]
+    #[allow(dead_code)]
+    struct Large<'a> {
+        #[substruct(Small)]
+        a: u64,
+        #[substruct(Borrowed)]
+        b: &'a str,
+        c: [u8; 64],
+    }
+
+    assert_eq!(std::mem::size_of::<Small>(), 8);
+}
+
+#[test]
+fn test_keep_markers() {
+    #[substruct(Marked, keep_markers, Plain)]
+    #[derive(Clone, Debug, PartialEq, serde::Serialize)]
+    struct Parent {
+        #[substruct(Marked, Plain)]
+        a: u32,
+        #[substruct(Marked)]
+        b: u32,
+    }
+
+    #[substruct(MarkedKind, keep_markers)]
+    #[derive(Debug, PartialEq)]
+    enum Kind {
+        #[substruct(MarkedKind)]
+        A,
+        B,
+    }
+
+    let marked = Marked { a: 1, b: 2 };
+    assert_eq!(
+        serde_json::to_value(&marked).unwrap(),
+        serde_json::json!({ "a": 1, "b": 2 })
+    );
+    assert_eq!(marked.into_parent(), Parent { a: 1, b: 2 });
+    assert_eq!(Plain { a: 1 }.a, 1);
+    assert_eq!(MarkedKind::A.into_kind(), Kind::A);
+    assert_eq!(Kind::B, Kind::B);
+}
+
+#[test]
+fn test_keep_markers_crate_path() {
+    // Stands in for the crate being renamed in `Cargo.toml`.
+    mod renamed {
+        pub use substruct::SubstructEmitted;
+    }
+
+    #[substruct(Marked, keep_markers, crate = "renamed")]
+    #[derive(Debug, PartialEq)]
+    struct Parent {
+        #[substruct(Marked)]
+        a: u32,
+    }
+
+    assert_eq!(Marked { a: 1 }.into_parent(), Parent { a: 1 });
+}
+
+#[test]
+fn test_partial_eq_parent() {
+    #[substruct(Summary, partial_eq_parent)]
+    #[derive(Clone, Debug, PartialEq)]
+    struct Report<T> {
+        #[substruct(Summary)]
+        #[substruct_rename(Summary, heading)]
+        title: String,
+        #[substruct(Summary)]
+        value: T,
+        details: Vec<String>,
+    }
+
+    let report = Report {
+        title: "report".to_string(),
+        value: 5,
+        details: vec!["a".to_string()],
+    };
+    let summary = Summary::from(report.clone());
+
+    // Only the included fields are compared.
+    let other = Report {
+        details: Vec::new(),
+        ..report.clone()
+    };
+    assert_eq!(summary, report);
+    assert_eq!(summary, other);
+    assert_ne!(summary, Report { value: 6, ..report });
+}
@@ -0,0 +1,73 @@
+//! Checks that the generated code only refers to `core` so that it can be
+//! used within `#![no_std]` crates.
+
+#![no_std]
+
+use substruct::substruct;
+
+#[derive(Debug, PartialEq)]
+pub struct RangeError;
+
+fn check(range: &Range) -> Result<(), RangeError> {
+    match range.start <= range.end {
+        true => Ok(()),
+        false => Err(RangeError),
+    }
+}
+
+#[substruct(
+    Start,
+    validate_parent = "check",
+    validate_error = "RangeError",
+    Both,
+    ref,
+    debug_entries,
+    Patch,
+    optional,
+    Filled,
+    fill_default,
+    Rest,
+    rest_default,
+    constructor,
+    default,
+    cow_enum
+)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Range {
+    #[substruct(Start, Both, Patch, Filled, Rest)]
+    pub start: u32,
+    #[substruct(Both, Patch)]
+    pub end: u32,
+    #[substruct(Start, default)]
+    pub step: u32,
+}
+
+#[substruct(Small)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    #[substruct(Small)]
+    Point,
+    Line(u32),
+}
+
+#[test]
+fn conversions() {
+    assert_eq!(Start { start: 6 }.into_range(5), Err(RangeError));
+
+    let both = Both { start: 1, end: 2 };
+    assert_eq!(both.to_range(3), both.clone().into_range(3));
+    assert_eq!(Both::from(both.clone().into_range(3)), both);
+
+    let mut range = Range::default();
+    Patch {
+        start: Some(4),
+        end: None,
+    }
+    .apply_to(&mut range);
+    assert_eq!(range.start, 4);
+
+    assert_eq!(Range::from(Filled { start: 2 }).end, 0);
+    assert_eq!(Rest::new(3).into_range().start, 3);
+    assert_eq!(Shape::from(Small::Point), Shape::Point);
+    assert!(Small::try_from(Shape::Line(1)).is_err());
+}
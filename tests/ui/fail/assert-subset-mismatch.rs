@@ -0,0 +1,11 @@
+use substruct::substruct;
+
+#[substruct(Patch, optional, assert_subset)]
+pub struct Config {
+    #[substruct(Patch)]
+    pub name: String,
+    #[substruct(Patch, ty = "u64")]
+    pub retries: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use substruct::substruct;
+
+#[substruct(B, C)]
+pub struct A {
+    #[substruct(#[cfg(any())] B, C)]
+    pub x: u32,
+}
+
+fn main() {}
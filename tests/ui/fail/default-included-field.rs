@@ -0,0 +1,11 @@
+use substruct::substruct;
+
+#[substruct(B)]
+pub struct A {
+    #[substruct(B)]
+    #[substruct_default(B, 5)]
+    pub x: u32,
+    pub y: u32,
+}
+
+fn main() {}
@@ -0,0 +1,47 @@
+//! Checks which fields end up with a doc override. Only the fields that no
+//! override applies to are reported as missing docs.
+
+#![deny(missing_docs)]
+
+use substruct::substruct;
+
+/// The parent.
+#[substruct(
+    /// A.
+    A,
+    /// B.
+    B,
+    /// C.
+    C
+)]
+pub struct Parent {
+    // Only documented in the parent, so A, B, and C are missing docs.
+    #[substruct(
+        /// Parent only.
+        Parent,
+        A, B, C
+    )]
+    pub only_parent: u32,
+
+    // B matches an undocumented argument first but the later override on
+    // `any(A, B, C)` still applies, so nothing is missing docs.
+    #[substruct(
+        /// The parent's docs.
+        Parent,
+        B,
+        /// Shared by A, B, and C.
+        any(A, B, C)
+    )]
+    pub shared: u32,
+
+    // The first matching override wins, so everything is documented.
+    #[substruct(
+        /// Specific to A.
+        A,
+        /// Everyone else.
+        all()
+    )]
+    pub first: u32,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use substruct::substruct;
+
+#[substruct(B)]
+pub struct A {
+    #[substruct(B)]
+    #[substruct_rename(B, value)]
+    pub x: u32,
+    #[substruct(B)]
+    #[substruct_rename(B, value)]
+    pub y: u32,
+    #[substruct(B)]
+    pub value: u32,
+}
+
+fn main() {}
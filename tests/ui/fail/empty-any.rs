@@ -0,0 +1,14 @@
+//! `any()` is vacuously false so the field would only be included in the
+//! parent, which is reported as an error.
+
+use substruct::substruct;
+
+#[substruct(A, B)]
+pub struct Parent {
+    #[substruct(any())]
+    pub a: u32,
+    #[substruct(A, B)]
+    pub b: u32,
+}
+
+fn main() {}
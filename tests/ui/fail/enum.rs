@@ -1,10 +0,0 @@
-use substruct::substruct;
-
-#[substruct(B)]
-pub enum A {
-    X,
-    Y,
-    Z,
-}
-
-fn main() {}
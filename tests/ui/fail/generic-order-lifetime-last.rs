@@ -0,0 +1,9 @@
+use substruct::substruct;
+
+#[substruct(B, generic_order(T, 'a))]
+pub struct A<'a, T> {
+    #[substruct(B)]
+    pub x: &'a T,
+}
+
+fn main() {}
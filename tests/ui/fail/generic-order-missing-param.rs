@@ -0,0 +1,11 @@
+use substruct::substruct;
+
+#[substruct(B, generic_order(U))]
+pub struct A<T, U> {
+    #[substruct(B)]
+    pub x: T,
+    #[substruct(B)]
+    pub y: U,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use substruct::substruct;
+
+#[substruct(B, C)]
+#[substruct_group(B = (C))]
+pub struct A {
+    #[substruct(B)]
+    pub x: u32,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+//! Only the child marked `hidden` is exempt from `missing_docs`.
+
+#![deny(missing_docs)]
+
+use substruct::substruct;
+
+#[substruct(Hidden, hidden, Visible)]
+pub struct Parent {
+    #[substruct(Hidden, Visible)]
+    a: u32,
+    b: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use substruct::substruct;
+
+#[substruct(B, method = "into-a")]
+pub struct A {
+    #[substruct(B)]
+    pub x: u32,
+}
+
+fn main() {}
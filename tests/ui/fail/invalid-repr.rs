@@ -0,0 +1,9 @@
+use substruct::substruct;
+
+#[substruct(Child, repr = "C,, u8")]
+pub struct Parent {
+    #[substruct(Child)]
+    pub a: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use substruct::substruct;
+
+#[substruct(B, vis = "public")]
+pub struct A {
+    #[substruct(B)]
+    pub x: u32,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use substruct::substruct;
+
+#[substruct(Point, iter)]
+pub struct Point3 {
+    #[substruct(Point)]
+    pub x: f32,
+    #[substruct(Point)]
+    pub y: f64,
+    pub z: f32,
+}
+
+fn main() {}
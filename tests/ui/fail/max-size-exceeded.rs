@@ -0,0 +1,10 @@
+use substruct::substruct;
+
+#[substruct(Header, max_size = 4)]
+pub struct Packet {
+    #[substruct(Header)]
+    pub id: u64,
+    pub body: [u8; 32],
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use substruct::substruct;
+
+#[substruct(B, merges(C), C)]
+pub struct A {
+    #[substruct(B, C)]
+    pub x: u32,
+    #[substruct(C)]
+    pub y: u32,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+#![deny(unused_must_use)]
+
+use substruct::substruct;
+
+#[substruct(Child, method_attr(must_use))]
+pub struct Parent {
+    #[substruct(Child)]
+    pub a: u32,
+    pub b: u32,
+}
+
+fn main() {
+    Child { a: 1 }.into_parent(2);
+}
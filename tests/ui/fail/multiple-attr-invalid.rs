@@ -3,9 +3,8 @@ use substruct::substruct;
 #[substruct(B, C)]
 struct A {
     #[substruct(B)]
-    #[substruct(C)]
+    #[substruct(C, =)]
     field: u32,
-
 }
 
 fn main() {}
@@ -0,0 +1,11 @@
+use substruct::substruct;
+
+#[substruct(Rgb, named(r, g))]
+pub struct Rgba(
+    #[substruct(Rgb)] pub u8,
+    #[substruct(Rgb)] pub u8,
+    #[substruct(Rgb)] pub u8,
+    pub u8,
+);
+
+fn main() {}
@@ -0,0 +1,8 @@
+use substruct::substruct;
+
+#[substruct(Compact, narrow(count: u32, missing: u8))]
+pub struct Stats {
+    pub count: u64,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+//! Only the field marked `substruct_nodoc` within `Undocumented` loses its
+//! docs.
+
+#![deny(missing_docs)]
+
+use substruct::substruct;
+
+/// The parent.
+#[substruct(
+    /// Keeps the field docs.
+    Documented,
+    /// Drops the field docs.
+    Undocumented
+)]
+pub struct Parent {
+    /// The field.
+    #[substruct(Documented, Undocumented)]
+    #[substruct_attr(Undocumented, substruct_nodoc)]
+    pub a: u32,
+}
+
+fn main() {}
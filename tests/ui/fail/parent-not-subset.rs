@@ -0,0 +1,12 @@
+use substruct::substruct;
+
+#[substruct(Medium, Small, parent = Medium)]
+pub struct Full {
+    #[substruct(Medium, Small)]
+    pub a: u32,
+    #[substruct(Small)]
+    pub b: u32,
+    pub c: u32,
+}
+
+fn main() {}
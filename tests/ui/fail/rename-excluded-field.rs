@@ -0,0 +1,10 @@
+use substruct::substruct;
+
+#[substruct(B, C)]
+pub struct A {
+    #[substruct(B)]
+    #[substruct_rename(C, renamed)]
+    pub x: u32,
+}
+
+fn main() {}
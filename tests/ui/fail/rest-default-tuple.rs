@@ -0,0 +1,7 @@
+use substruct::substruct;
+
+#[substruct(B, rest_default)]
+#[derive(Default)]
+pub struct A(#[substruct(B)] pub u32, pub u32);
+
+fn main() {}
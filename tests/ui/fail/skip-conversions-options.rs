@@ -0,0 +1,20 @@
+use substruct::substruct;
+
+#[substruct(
+    A,
+    skip_conversions,
+    partial_eq_parent,
+    assert_subset,
+    merges(B),
+    method = "foo",
+    B
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Test {
+    #[substruct(A)]
+    pub x: u32,
+    #[substruct(B)]
+    pub y: u32,
+}
+
+fn main() {}
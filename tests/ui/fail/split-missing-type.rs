@@ -0,0 +1,9 @@
+use substruct::substruct;
+
+#[substruct(B)]
+pub struct A {
+    #[substruct(B, split(start, end))]
+    pub range: std::ops::Range<u32>,
+}
+
+fn main() {}
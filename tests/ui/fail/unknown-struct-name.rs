@@ -0,0 +1,11 @@
+use substruct::substruct;
+
+#[substruct(Query, Filter)]
+pub struct Request {
+    #[substruct(Query, Fliter)]
+    pub filter: String,
+    #[substruct(any(Query, not(Qurey)))]
+    pub limit: u32,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use substruct::substruct;
+
+#[substruct(A, B, C)]
+pub struct Parent {
+    #[substruct(all(B, C))]
+    pub a: u32,
+    #[substruct(A, not(any(A, B, C, Parent)))]
+    pub b: u32,
+    // Literal `false` is allowed.
+    #[substruct(false)]
+    pub c: u32,
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+use std::error::Error;
+
+use substruct::substruct;
+
+#[substruct(Compact)]
+#[derive(Debug)]
+pub struct Stats {
+    #[substruct(Compact, ty = "u8", via = "try_into")]
+    pub count: u64,
+    pub name: String,
+}
+
+fn compact(stats: Stats) -> Result<Compact, Box<dyn Error>> {
+    Ok(Compact::try_from(stats)?)
+}
+
+fn main() {
+    let error = compact(Stats {
+        count: 300,
+        name: "stats".into(),
+    })
+    .unwrap_err();
+
+    assert_eq!(error.to_string(), "failed to convert the `count` field");
+    assert!(compact(Stats {
+        count: 3,
+        name: "stats".into(),
+    })
+    .is_ok());
+}
@@ -0,0 +1,20 @@
+use substruct::substruct;
+
+#[substruct(Dbg, debug_entries)]
+pub struct Parent {
+    #[substruct(Dbg)]
+    pub a: u32,
+    #[cfg(any())]
+    #[substruct(Dbg)]
+    pub b: u32,
+    #[cfg(all())]
+    #[substruct(Dbg)]
+    pub c: u32,
+}
+
+fn main() {
+    let parent = Parent { a: 1, c: 3 };
+    let names: Vec<_> = parent.dbg_entries().map(|(name, _)| name).collect();
+
+    assert_eq!(names, ["a", "c"]);
+}
@@ -0,0 +1,19 @@
+//! Checks that `#[doc(hidden)]` is kept when the docs of a struct are
+//! overridden. The fields of hidden structs don't need docs.
+
+#![deny(missing_docs)]
+
+use substruct::substruct;
+
+/// The parent.
+#[substruct(
+    /// The child.
+    Child
+)]
+#[doc(hidden)]
+pub struct Parent {
+    #[substruct(Child)]
+    pub value: u32,
+}
+
+fn main() {}
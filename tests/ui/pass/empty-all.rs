@@ -0,0 +1,18 @@
+//! `all()` is vacuously true so the field is included in every struct.
+
+use substruct::substruct;
+
+#[substruct(A, B)]
+pub struct Parent {
+    #[substruct(all())]
+    pub a: u32,
+    pub b: u32,
+}
+
+fn main() {
+    let a = A { a: 1 };
+    let b = B { a: 2 };
+
+    assert_eq!(a.into_parent(3).a, 1);
+    assert_eq!(b.into_parent(3).a, 2);
+}
@@ -0,0 +1,22 @@
+//! Multiple `#[substruct]` attributes on a field are merged together.
+
+use substruct::substruct;
+
+#[substruct(B, C, D)]
+struct A {
+    #[substruct(B)]
+    #[substruct(C)]
+    field: u32,
+    #[substruct(D)]
+    other: u32,
+}
+
+fn main() {
+    let b = B { field: 1 };
+    let c = C { field: 2 };
+    let d = D { other: 3 };
+
+    assert_eq!(b.into_a(4).field, 1);
+    assert_eq!(c.into_a(4).field, 2);
+    assert_eq!(d.into_a(4).other, 3);
+}
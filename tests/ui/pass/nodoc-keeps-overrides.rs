@@ -0,0 +1,23 @@
+//! `substruct_nodoc` only removes the inherited doc comments. Doc overrides
+//! within `#[substruct]` and other `#[doc]` attributes are kept, so nothing
+//! here is missing docs.
+
+#![deny(missing_docs)]
+
+use substruct::substruct;
+
+/// The parent.
+#[substruct(
+    /// The summary.
+    Summary
+)]
+#[substruct_attr(Summary, substruct_nodoc)]
+pub struct Parent {
+    /// Hidden from the docs.
+    #[substruct(Summary)]
+    #[substruct_attr(Summary, substruct_nodoc)]
+    #[doc(hidden)]
+    pub a: u32,
+}
+
+fn main() {}
@@ -0,0 +1,33 @@
+use substruct::substruct;
+
+#[substruct(B, constructor, C, constructor)]
+#[derive(Clone, Copy)]
+pub union A {
+    #[substruct(B, C)]
+    pub x: u64,
+    #[substruct(B)]
+    pub y: u32,
+    pub z: u16,
+}
+
+// Unions don't get any conversions, so these don't conflict with anything
+// emitted by the macro.
+impl From<C> for A {
+    fn from(value: C) -> Self {
+        A { x: unsafe { value.x } }
+    }
+}
+
+impl From<A> for B {
+    fn from(value: A) -> Self {
+        B::new_y(unsafe { value.y })
+    }
+}
+
+fn main() {
+    let a = A::from(C::new_x(77));
+    let b = B::from(a);
+    let _ = B::new_x(5);
+    let _ = A { z: 3 };
+    let _ = b;
+}
@@ -0,0 +1,14 @@
+//! Generating structs from a unit struct emits identical unit structs, without
+//! any conversions since there are no fields to convert.
+
+use substruct::substruct;
+
+#[substruct(A, B)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Unit;
+
+fn main() {
+    let _: Unit = Unit;
+    assert_eq!(A, A::default());
+    assert_eq!(B, B);
+}